@@ -0,0 +1,392 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::database::Database;
+use crate::distraction_blocker::BlockedItem;
+use crate::url_matcher::glob_match;
+
+#[cfg(target_os = "linux")]
+use crate::distraction_blocker::{AppBlockAction, BlockVerdict, BlockerManager};
+#[cfg(target_os = "linux")]
+use crate::distraction_log;
+#[cfg(target_os = "linux")]
+use crate::focus_manager::FocusManager;
+#[cfg(target_os = "linux")]
+use chrono::Utc;
+#[cfg(target_os = "linux")]
+use std::collections::HashSet;
+#[cfg(target_os = "linux")]
+use tauri::{Emitter, Manager};
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub const APP_DETECTED_EVENT: &str = "distraction-app-detected";
+
+/// Processes currently suspended, so they can be resumed after a crash.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const SUSPENDED_KEY: &str = "app_watcher_suspended";
+
+/// Literal characters an Application pattern's name needs, so that a stray
+/// `*` or `s*` can't take down every process the user runs.
+const MIN_PATTERN_CHARS: usize = 3;
+
+#[cfg(target_os = "linux")]
+const SCAN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// A running process, as far as Application items are concerned.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    /// Short name from /proc/<pid>/comm, truncated by the kernel to 15 bytes.
+    pub name: String,
+    /// Full path of the executable, when readable.
+    pub exe: Option<String>,
+}
+
+/// Rejects Application patterns broad enough to match most processes: the
+/// name part (after the last `/`) needs a few characters besides `*`.
+pub fn validate_pattern(pattern: &str) -> Result<(), String> {
+    let name = pattern.trim().rsplit('/').next().unwrap_or_default();
+    if name.chars().filter(|&c| c != '*').count() < MIN_PATTERN_CHARS {
+        return Err(format!(
+            "Application pattern '{}' is too broad; name at least {} characters of the program",
+            pattern.trim(),
+            MIN_PATTERN_CHARS
+        ));
+    }
+    Ok(())
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+impl ProcessInfo {
+    /// Explains why this process matches an Application item's pattern, or
+    /// `None` if it does not. Patterns containing `/` are matched against the
+    /// executable path, others against the process and executable names;
+    /// `*` works as a wildcard in both. Patterns failing `validate_pattern`,
+    /// e.g. from settings saved before it existed, match nothing.
+    pub fn matches(&self, pattern: &str) -> Option<String> {
+        validate_pattern(pattern).ok()?;
+        let pattern = pattern.trim().to_lowercase();
+
+        if pattern.contains('/') {
+            let exe = self.exe.as_deref()?;
+            return glob_match(&pattern, &exe.to_lowercase())
+                .then(|| format!("executable is {}", exe));
+        }
+
+        let exe_name = self.exe.as_deref().and_then(|exe| exe.rsplit('/').next());
+        std::iter::once(self.name.as_str())
+            .chain(exe_name)
+            .find(|name| glob_match(&pattern, &name.to_lowercase()))
+            .map(|name| format!("process name is {}", name))
+    }
+}
+
+/// What the watcher did about a blocked application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppAction {
+    Warned,
+    Terminated,
+    Suspended,
+    /// Block mode applied but the process could not be signalled.
+    Failed,
+}
+
+/// Payload of the `distraction-app-detected` event.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppDetected {
+    pub pid: u32,
+    pub process_name: String,
+    pub item: BlockedItem,
+    pub reason: String,
+    pub action: AppAction,
+}
+
+/// A process stopped with SIGSTOP, identified by pid and start time so a
+/// reused pid is never resumed by mistake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct SuspendedProcess {
+    pub pid: u32,
+    /// Clock ticks after boot, from /proc/<pid>/stat.
+    pub start_time: u64,
+}
+
+/// Watches running processes for Application items while blocking applies.
+/// Only implemented on Linux, where it reads /proc.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct AppWatcher {
+    /// Processes stopped with SIGSTOP, resumed once they are no longer blocked.
+    /// Mirrored to the settings table whenever it changes.
+    suspended: Mutex<HashMap<u32, SuspendedProcess>>,
+    /// Last action reported per process, so each change is announced once.
+    reported: Mutex<HashMap<u32, AppAction>>,
+}
+
+impl Default for AppWatcher {
+    fn default() -> Self {
+        AppWatcher {
+            suspended: Mutex::new(HashMap::new()),
+            reported: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl AppWatcher {
+    /// Checks every running process against the blocklist once.
+    fn scan(&self, app: &AppHandle) {
+        let processes = match list_processes() {
+            Ok(processes) => processes,
+            Err(e) => {
+                eprintln!("Failed to list processes: {}", e);
+                return;
+            }
+        };
+
+        let blocker = app.state::<BlockerManager>();
+        let session = app.state::<FocusManager>().active_session();
+        let app_action = blocker.app_action();
+        let now = Utc::now();
+
+        let mut suspended = self.suspended.lock().unwrap();
+        let mut reported = self.reported.lock().unwrap();
+        let previously_suspended = suspended.clone();
+
+        let running: HashSet<u32> = processes.iter().map(|process| process.pid).collect();
+        suspended.retain(|pid, _| running.contains(pid));
+        reported.retain(|pid, _| running.contains(pid));
+
+        for process in &processes {
+            let verdict = blocker.evaluate_process(process, now, session);
+            let (item, reason, blocked) = match &verdict {
+                BlockVerdict::Warn { item, reason } => (item, reason, false),
+                BlockVerdict::Blocked { item, reason } => (item, reason, true),
+                BlockVerdict::Allowed | BlockVerdict::Overridden { .. } => {
+                    if suspended.remove(&process.pid).is_some() {
+                        signal_or_log(process.pid, libc::SIGCONT);
+                    }
+                    reported.remove(&process.pid);
+                    continue;
+                }
+            };
+
+            let action = if !blocked {
+                AppAction::Warned
+            } else {
+                match app_action {
+                    AppBlockAction::Terminate => {
+                        // Sent on every scan in case the process ignores it;
+                        // a stopped process only acts on it once resumed
+                        let result = send_signal(process.pid, libc::SIGTERM);
+                        if suspended.remove(&process.pid).is_some() {
+                            signal_or_log(process.pid, libc::SIGCONT);
+                        }
+                        match result {
+                            Ok(()) => AppAction::Terminated,
+                            Err(e) => {
+                                eprintln!("Failed to terminate process {}: {}", process.pid, e);
+                                AppAction::Failed
+                            }
+                        }
+                    }
+                    AppBlockAction::Suspend if suspended.contains_key(&process.pid) => {
+                        AppAction::Suspended
+                    }
+                    AppBlockAction::Suspend => match suspend(process.pid) {
+                        Ok(stopped) => {
+                            suspended.insert(process.pid, stopped);
+                            AppAction::Suspended
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to suspend process {}: {}", process.pid, e);
+                            AppAction::Failed
+                        }
+                    },
+                }
+            };
+
+            if reported.insert(process.pid, action) != Some(action) {
+                let db = app.state::<Database>();
+                if let Err(e) = distraction_log::log_attempt(&db, &verdict, &process.name, session)
+                {
+                    eprintln!("Failed to log distraction attempt: {}", e);
+                }
+
+                let _ = app.emit(
+                    APP_DETECTED_EVENT,
+                    AppDetected {
+                        pid: process.pid,
+                        process_name: process.name.clone(),
+                        item: item.clone(),
+                        reason: reason.clone(),
+                        action,
+                    },
+                );
+            }
+        }
+
+        if *suspended != previously_suspended {
+            save_suspended(&app.state::<Database>(), &suspended);
+        }
+    }
+}
+
+/// Stops `pid`. Its start time is read first: without it the process
+/// couldn't be told apart from a reused pid after a crash, so it isn't stopped.
+#[cfg(target_os = "linux")]
+fn suspend(pid: u32) -> Result<SuspendedProcess, String> {
+    let start_time = process_start_time(pid).ok_or_else(|| "process exited".to_string())?;
+    send_signal(pid, libc::SIGSTOP)?;
+    Ok(SuspendedProcess { pid, start_time })
+}
+
+#[cfg(target_os = "linux")]
+fn save_suspended(db: &Database, suspended: &HashMap<u32, SuspendedProcess>) {
+    let mut processes: Vec<SuspendedProcess> = suspended.values().copied().collect();
+    processes.sort_by_key(|process| process.pid);
+    if let Err(e) = db.save_setting(SUSPENDED_KEY, &processes) {
+        eprintln!("Failed to save suspended processes: {}", e);
+    }
+}
+
+/// Start time of `pid` in clock ticks after boot: field 22 of
+/// /proc/<pid>/stat, counted after the parenthesised command name.
+#[cfg(target_os = "linux")]
+fn process_start_time(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// Processes that must never be signalled: init, this app, the process that
+/// launched it and its session leader, typically the desktop session.
+#[cfg(target_os = "linux")]
+fn protected_pids() -> HashSet<u32> {
+    // SAFETY: getppid and getsid have no memory-safety preconditions
+    let (parent, session) = unsafe { (libc::getppid(), libc::getsid(0)) };
+    [1, std::process::id() as libc::pid_t, parent, session]
+        .into_iter()
+        .filter_map(|pid| u32::try_from(pid).ok())
+        .collect()
+}
+
+/// Lists processes from /proc, skipping protected ones and any that exit
+/// mid-scan.
+#[cfg(target_os = "linux")]
+pub fn list_processes() -> std::io::Result<Vec<ProcessInfo>> {
+    let protected = protected_pids();
+    let mut processes = Vec::new();
+
+    for entry in std::fs::read_dir("/proc")? {
+        let Ok(entry) = entry else { continue };
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse().ok())
+        else {
+            continue;
+        };
+        if protected.contains(&pid) {
+            continue;
+        }
+
+        let Ok(name) = std::fs::read_to_string(entry.path().join("comm")) else {
+            continue;
+        };
+        // Unreadable for other users' processes and kernel threads
+        let exe = std::fs::read_link(entry.path().join("exe"))
+            .ok()
+            .map(|path| {
+                let path = path.to_string_lossy();
+                path.strip_suffix(" (deleted)").unwrap_or(&path).to_string()
+            });
+
+        processes.push(ProcessInfo {
+            pid,
+            name: name.trim_end().to_string(),
+            exe,
+        });
+    }
+
+    Ok(processes)
+}
+
+#[cfg(target_os = "linux")]
+fn send_signal(pid: u32, signal: libc::c_int) -> Result<(), String> {
+    let pid = libc::pid_t::try_from(pid).map_err(|_| format!("Invalid pid {}", pid))?;
+    // SAFETY: kill has no memory-safety preconditions
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn signal_or_log(pid: u32, signal: libc::c_int) {
+    if let Err(e) = send_signal(pid, signal) {
+        eprintln!("Failed to signal process {}: {}", pid, e);
+    }
+}
+
+/// Starts scanning processes in the background. Reading /proc blocks, so the
+/// watcher gets its own thread rather than a runtime task.
+#[cfg(target_os = "linux")]
+pub fn start_watcher(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        app.state::<AppWatcher>().scan(&app);
+        std::thread::sleep(SCAN_INTERVAL);
+    });
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn start_watcher(_app: &AppHandle) {}
+
+/// Resumes every process the watcher suspended, e.g. when the app exits.
+#[cfg(target_os = "linux")]
+pub fn resume_suspended(app: &AppHandle) {
+    let watcher = app.state::<AppWatcher>();
+    let mut suspended = watcher.suspended.lock().unwrap();
+    for pid in suspended.keys() {
+        signal_or_log(*pid, libc::SIGCONT);
+    }
+    suspended.clear();
+    save_suspended(&app.state::<Database>(), &suspended);
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn resume_suspended(_app: &AppHandle) {}
+
+/// Resumes processes left suspended by a previous run that crashed or was
+/// killed before `resume_suspended` could run. Processes whose start time no
+/// longer matches have exited and their pid may have been reused, so they
+/// are left alone.
+#[cfg(target_os = "linux")]
+pub fn resume_orphaned(db: &Database) {
+    let processes: Vec<SuspendedProcess> = match db.load_setting(SUSPENDED_KEY) {
+        Ok(processes) => processes.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("Failed to load suspended processes: {}", e);
+            return;
+        }
+    };
+    if processes.is_empty() {
+        return;
+    }
+
+    for process in processes {
+        if process_start_time(process.pid) == Some(process.start_time) {
+            signal_or_log(process.pid, libc::SIGCONT);
+        }
+    }
+    save_suspended(db, &HashMap::new());
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn resume_orphaned(_db: &Database) {}
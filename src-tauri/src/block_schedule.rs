@@ -0,0 +1,141 @@
+use chrono::{DateTime, Datelike, Local, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// When blocking applies, in local time. Used both globally and per item.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlockSchedule {
+    /// Days the schedule covers; empty means every day.
+    pub days: Vec<Weekday>,
+    /// Time windows within those days; empty means all day.
+    pub windows: Vec<TimeWindow>,
+}
+
+/// A local time range as "HH:MM" strings. A window whose end is before its
+/// start runs past midnight, e.g. 22:00–02:00.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeWindow {
+    pub start: String,
+    pub end: String,
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid time '{}', expected HH:MM", value))
+}
+
+impl TimeWindow {
+    fn bounds(&self) -> Result<(NaiveTime, NaiveTime), String> {
+        Ok((parse_time(&self.start)?, parse_time(&self.end)?))
+    }
+}
+
+impl BlockSchedule {
+    pub fn validate(&self) -> Result<(), String> {
+        for window in &self.windows {
+            let (start, end) = window.bounds()?;
+            if start == end {
+                return Err(format!(
+                    "Time window {}–{} is empty; leave out windows to block all day",
+                    window.start, window.end
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn covers_day(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    /// Whether blocking applies at `now`. The part of an overnight window
+    /// after midnight belongs to the day the window started on.
+    pub fn is_active_at(&self, now: DateTime<Local>) -> bool {
+        let today = now.weekday();
+        if self.windows.is_empty() {
+            return self.covers_day(today);
+        }
+
+        let time = now.time();
+        self.windows.iter().any(|window| {
+            // Unparseable windows are rejected by validate; never match them
+            let Ok((start, end)) = window.bounds() else {
+                return false;
+            };
+
+            if start < end {
+                self.covers_day(today) && start <= time && time < end
+            } else {
+                (self.covers_day(today) && time >= start)
+                    || (self.covers_day(today.pred()) && time < end)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn window(start: &str, end: &str) -> TimeWindow {
+        TimeWindow {
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    /// Local time on a day in October 2026; the 19th is a Monday.
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 10, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn daytime_window_is_half_open() {
+        let schedule = BlockSchedule {
+            days: vec![Weekday::Mon],
+            windows: vec![window("09:00", "17:00")],
+        };
+
+        assert!(schedule.is_active_at(at(19, 9, 0)));
+        assert!(schedule.is_active_at(at(19, 16, 59)));
+        assert!(!schedule.is_active_at(at(19, 17, 0)));
+        assert!(!schedule.is_active_at(at(20, 10, 0)));
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_its_start_day() {
+        let schedule = BlockSchedule {
+            days: vec![Weekday::Fri],
+            windows: vec![window("22:00", "02:00")],
+        };
+
+        assert!(schedule.is_active_at(at(23, 23, 0)));
+        // Saturday 01:00 is still Friday night
+        assert!(schedule.is_active_at(at(24, 1, 0)));
+        assert!(!schedule.is_active_at(at(24, 2, 0)));
+        // Friday 01:00 belongs to Thursday night
+        assert!(!schedule.is_active_at(at(23, 1, 0)));
+    }
+
+    #[test]
+    fn empty_days_and_windows_mean_always() {
+        let schedule = BlockSchedule::default();
+        assert!(schedule.is_active_at(at(18, 3, 0)));
+        assert!(schedule.is_active_at(at(21, 15, 30)));
+    }
+
+    #[test]
+    fn rejects_invalid_and_empty_windows() {
+        let schedule = |start, end| BlockSchedule {
+            days: vec![],
+            windows: vec![window(start, end)],
+        };
+
+        assert!(schedule("9", "10:00").validate().is_err());
+        assert!(schedule("10:00", "10:00").validate().is_err());
+        assert!(schedule("22:00", "02:00").validate().is_ok());
+    }
+}
@@ -0,0 +1,544 @@
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager, State};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use url::Url;
+
+use crate::database::{Database, DbError};
+use crate::distraction_blocker::BlockerManager;
+use crate::distraction_log;
+use crate::focus_manager::FocusManager;
+use crate::url_matcher::ParsedUrl;
+
+const SETTINGS_KEY: &str = "blocker_server_settings";
+
+const DEFAULT_PORT: u16 = 47615;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_HEAD_BYTES: usize = 8 * 1024;
+const MAX_BODY_BYTES: usize = 16 * 1024;
+
+/// Settings for the localhost endpoint that browser extensions query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Sent by clients as `Authorization: Bearer <token>`.
+    pub token: String,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        ServerSettings {
+            enabled: false,
+            port: DEFAULT_PORT,
+            token: new_token(),
+        }
+    }
+}
+
+fn new_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Compares tokens in time independent of where they first differ, so a
+/// local client cannot recover the token byte by byte. Only the length,
+/// which is the same for every generated token, can leak.
+fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// HTTP server on 127.0.0.1 exposing the blocker to a companion browser
+/// extension:
+///
+/// - `GET /check?url=<url>` returns the `BlockVerdict` for a URL
+/// - `GET /focus` returns the current `FocusState`
+/// - `POST /override` with `{"duration": <seconds>}` requests an override
+///   when no friction is configured
+/// - `POST /override/challenge` with `{"duration": <seconds>}` starts an
+///   override challenge
+/// - `POST /override/complete` with `{"challenge_id", "phrase", "reason"}`
+///   answers it and grants the override
+///
+/// Refused overrides get a 403 with the reason.
+pub struct BlockerServer {
+    settings: Mutex<ServerSettings>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Default for BlockerServer {
+    fn default() -> Self {
+        BlockerServer {
+            settings: Mutex::new(ServerSettings::default()),
+            task: Mutex::new(None),
+        }
+    }
+}
+
+impl BlockerServer {
+    /// Loads saved settings, saving the defaults on first run so the
+    /// generated token stays the same across restarts.
+    pub fn load_settings(&self, db: &Database) -> Result<(), DbError> {
+        let mut settings = self.settings.lock().unwrap();
+        match db.load_setting::<ServerSettings>(SETTINGS_KEY)? {
+            Some(stored) if !stored.token.is_empty() => *settings = stored,
+            Some(stored) => {
+                *settings = ServerSettings {
+                    token: new_token(),
+                    ..stored
+                };
+                db.save_setting(SETTINGS_KEY, &*settings)?;
+            }
+            None => db.save_setting(SETTINGS_KEY, &*settings)?,
+        }
+        Ok(())
+    }
+
+    /// Starts listening if the server is enabled.
+    pub fn start(&self, app: &AppHandle) -> Result<(), String> {
+        let settings = self.settings.lock().unwrap().clone();
+        let task = spawn_server(app, &settings)?;
+        if let Some(previous) = std::mem::replace(&mut *self.task.lock().unwrap(), task) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Stops the server and waits for its listener to be released.
+    async fn stop(&self) {
+        let task = self.task.lock().unwrap().take();
+        if let Some(task) = task {
+            task.abort();
+            let _ = task.await;
+        }
+    }
+
+    /// Restarts the server with `updated` and saves it. If the new settings
+    /// cannot be served, the previous server is brought back instead.
+    async fn apply(
+        &self,
+        app: &AppHandle,
+        db: &Database,
+        updated: ServerSettings,
+    ) -> Result<ServerSettings, String> {
+        if updated.port == 0 {
+            return Err("Port must be between 1 and 65535".to_string());
+        }
+
+        self.stop().await;
+        let task = match spawn_server(app, &updated) {
+            Ok(task) => task,
+            Err(e) => {
+                if let Err(restore) = self.start(app) {
+                    eprintln!("Failed to restart blocker server: {}", restore);
+                }
+                return Err(e);
+            }
+        };
+        *self.task.lock().unwrap() = task;
+
+        db.save_setting(SETTINGS_KEY, &updated)
+            .map_err(|e| e.to_string())?;
+        *self.settings.lock().unwrap() = updated.clone();
+        Ok(updated)
+    }
+}
+
+/// Binds synchronously so port conflicts are reported to the caller, then
+/// serves connections in the background.
+fn spawn_server(
+    app: &AppHandle,
+    settings: &ServerSettings,
+) -> Result<Option<JoinHandle<()>>, String> {
+    if !settings.enabled {
+        return Ok(None);
+    }
+
+    let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, settings.port))
+        .map_err(|e| format!("Failed to listen on 127.0.0.1:{}: {}", settings.port, e))?;
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+
+    let app = app.clone();
+    let token = settings.token.clone();
+    Ok(Some(tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Blocker server failed to start: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let app = app.clone();
+                    let token = token.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = handle_connection(stream, &app, &token).await {
+                            eprintln!("Blocker server connection failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => eprintln!("Blocker server failed to accept a connection: {}", e),
+            }
+        }
+    })))
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    /// Header names are lowercased.
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn token(&self) -> Option<&str> {
+        self.header("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+    }
+
+    /// The JSON body, with an empty body treated as all defaults.
+    fn json<T: DeserializeOwned + Default>(&self) -> Result<T, Response> {
+        if self.body.is_empty() {
+            return Ok(T::default());
+        }
+        serde_json::from_slice(&self.body)
+            .map_err(|e| Response::error(400, format!("Invalid request body: {}", e)))
+    }
+}
+
+struct Response {
+    status: u16,
+    body: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct OverrideRequest {
+    duration: u32,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct CompleteOverrideRequest {
+    challenge_id: String,
+    phrase: Option<String>,
+    reason: Option<String>,
+}
+
+#[derive(Serialize)]
+struct OverrideResponse {
+    granted: bool,
+    until: String,
+}
+
+impl Response {
+    fn json<T: Serialize>(body: &T) -> Self {
+        match serde_json::to_string(body) {
+            Ok(body) => Response {
+                status: 200,
+                body: Some(body),
+            },
+            Err(e) => Response::error(500, format!("Failed to encode response: {}", e)),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        let body = serde_json::to_string(&ErrorBody {
+            error: message.into(),
+        })
+        .unwrap_or_default();
+        Response {
+            status,
+            body: Some(body),
+        }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        let reason = match self.status {
+            200 => "OK",
+            204 => "No Content",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            _ => "Internal Server Error",
+        };
+        let body = self.body.unwrap_or_default();
+
+        // Extensions call from their own origin, so allow CORS; the token
+        // is what keeps web pages out
+        format!(
+            "HTTP/1.1 {} {}\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Access-Control-Allow-Origin: *\r\n\
+             Access-Control-Allow-Headers: Authorization, Content-Type\r\n\
+             Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+             Connection: close\r\n\
+             \r\n\
+             {}",
+            self.status,
+            reason,
+            body.len(),
+            body
+        )
+        .into_bytes()
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    app: &AppHandle,
+    token: &str,
+) -> std::io::Result<()> {
+    let response = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(request)) => route(&request, app, token),
+        Ok(Err(message)) => Response::error(400, message),
+        Err(_) => Response::error(408, "Timed out reading request"),
+    };
+
+    stream.write_all(&response.into_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+
+    let head_end = loop {
+        if let Some(index) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break index;
+        }
+        if buffer.len() > MAX_HEAD_BYTES {
+            return Err("Request headers are too large".to_string());
+        }
+        let read = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if read == 0 {
+            return Err("Connection closed before the request was complete".to_string());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = std::str::from_utf8(&buffer[..head_end])
+        .map_err(|_| "Request headers are not valid UTF-8".to_string())?;
+    let mut lines = head.split("\r\n");
+
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Err("Malformed request line".to_string());
+    };
+
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let content_length = match headers.iter().find(|(name, _)| name == "content-length") {
+        Some((_, value)) => value
+            .parse::<usize>()
+            .map_err(|_| "Invalid Content-Length".to_string())?,
+        None => 0,
+    };
+    if content_length > MAX_BODY_BYTES {
+        return Err("Request body is too large".to_string());
+    }
+
+    let mut body = buffer[head_end + 4..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if read == 0 {
+            return Err("Connection closed before the body was complete".to_string());
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    let url = Url::parse(&format!("http://localhost{}", target))
+        .map_err(|_| format!("Malformed request target '{}'", target))?;
+
+    Ok(Request {
+        method: method.to_string(),
+        path: url.path().to_string(),
+        query: url.query_pairs().into_owned().collect(),
+        headers,
+        body,
+    })
+}
+
+fn route(request: &Request, app: &AppHandle, token: &str) -> Response {
+    // CORS preflight requests never carry credentials
+    if request.method == "OPTIONS" {
+        return Response {
+            status: 204,
+            body: None,
+        };
+    }
+    if !request
+        .token()
+        .is_some_and(|given| token_matches(given, token))
+    {
+        return Response::error(401, "Missing or invalid token");
+    }
+
+    let blocker = app.state::<BlockerManager>();
+    let focus = app.state::<FocusManager>();
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/check") => {
+            let Some(url) = request.query("url") else {
+                return Response::error(400, "Missing 'url' query parameter");
+            };
+            let parsed = match ParsedUrl::parse(url) {
+                Ok(parsed) => parsed,
+                Err(e) => return Response::error(400, e),
+            };
+
+            let session = focus.active_session();
+            let verdict = blocker.evaluate(&parsed, Utc::now(), session);
+            let db = app.state::<Database>();
+            if let Err(e) = distraction_log::log_attempt(&db, &verdict, url, session) {
+                eprintln!("Failed to log distraction attempt: {}", e);
+            }
+            Response::json(&verdict)
+        }
+        ("GET", "/focus") => {
+            let state = focus.state.lock().unwrap().clone();
+            Response::json(&state)
+        }
+        ("POST", "/override") => {
+            let body: OverrideRequest = match request.json() {
+                Ok(body) => body,
+                Err(response) => return response,
+            };
+            let db = app.state::<Database>();
+            override_response(blocker.request_override(app, &db, body.duration))
+        }
+        ("POST", "/override/challenge") => {
+            let body: OverrideRequest = match request.json() {
+                Ok(body) => body,
+                Err(response) => return response,
+            };
+            let db = app.state::<Database>();
+            match blocker.begin_override(&db, body.duration) {
+                Ok(challenge) => Response::json(&challenge),
+                Err(e) => Response::error(403, e),
+            }
+        }
+        ("POST", "/override/complete") => {
+            let body: CompleteOverrideRequest = match request.json() {
+                Ok(body) => body,
+                Err(response) => return response,
+            };
+            let db = app.state::<Database>();
+            override_response(blocker.complete_override(
+                app,
+                &db,
+                &body.challenge_id,
+                body.phrase.as_deref(),
+                body.reason.as_deref(),
+            ))
+        }
+        (_, "/check" | "/focus" | "/override" | "/override/challenge" | "/override/complete") => {
+            Response::error(405, "Method not allowed")
+        }
+        _ => Response::error(404, "Not found"),
+    }
+}
+
+fn override_response(result: Result<DateTime<Utc>, String>) -> Response {
+    match result {
+        Ok(until) => Response::json(&OverrideResponse {
+            granted: true,
+            until: until.to_rfc3339(),
+        }),
+        Err(e) => Response::error(403, e),
+    }
+}
+
+#[tauri::command]
+pub async fn get_blocker_server_settings(
+    state: State<'_, BlockerServer>,
+) -> Result<ServerSettings, String> {
+    let settings = state.settings.lock().unwrap();
+    Ok(settings.clone())
+}
+
+#[tauri::command]
+pub async fn update_blocker_server_settings(
+    enabled: bool,
+    port: u16,
+    app: AppHandle,
+    state: State<'_, BlockerServer>,
+    db: State<'_, Database>,
+) -> Result<ServerSettings, String> {
+    let current = state.settings.lock().unwrap().clone();
+    state
+        .apply(
+            &app,
+            &db,
+            ServerSettings {
+                enabled,
+                port,
+                ..current
+            },
+        )
+        .await
+}
+
+/// Issues a new token, disconnecting clients that use the old one.
+#[tauri::command]
+pub async fn regenerate_blocker_server_token(
+    app: AppHandle,
+    state: State<'_, BlockerServer>,
+    db: State<'_, Database>,
+) -> Result<ServerSettings, String> {
+    let current = state.settings.lock().unwrap().clone();
+    state
+        .apply(
+            &app,
+            &db,
+            ServerSettings {
+                token: new_token(),
+                ..current
+            },
+        )
+        .await
+}
@@ -0,0 +1,458 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::net::IpAddr;
+use tauri::State;
+
+use crate::block_schedule::BlockSchedule;
+use crate::database::Database;
+use crate::distraction_blocker::{BlockItemType, BlockedItem, BlockerManager};
+use crate::url_matcher::{HostRule, UrlPattern};
+
+const BUNDLE_VERSION: u32 = 1;
+
+pub enum BlocklistFormat {
+    /// `BlocklistBundle` JSON, or a bare array of items.
+    Json,
+    /// One domain per line; hosts-file lines such as `0.0.0.0 example.com` also work.
+    Domains,
+    /// uBlock Origin / Adblock Plus network rules like `||example.com^`.
+    Adblock,
+}
+
+impl BlocklistFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "json" => Ok(BlocklistFormat::Json),
+            "domains" | "txt" | "hosts" => Ok(BlocklistFormat::Domains),
+            "adblock" | "ublock" => Ok(BlocklistFormat::Adblock),
+            _ => Err(format!(
+                "Unsupported blocklist format '{}', expected json, domains or adblock",
+                value
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlocklistBundle {
+    pub version: u32,
+    pub items: Vec<BlockedItem>,
+}
+
+/// A bundle entry; ids are reassigned on import, so only these fields matter.
+#[derive(Deserialize)]
+struct BundleEntry {
+    #[serde(default)]
+    name: String,
+    url_pattern: String,
+    #[serde(default = "default_item_type")]
+    item_type: BlockItemType,
+    #[serde(default = "default_active")]
+    is_active: bool,
+    #[serde(default)]
+    schedule: Option<BlockSchedule>,
+}
+
+fn default_item_type() -> BlockItemType {
+    BlockItemType::Website
+}
+
+fn default_active() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BundleInput {
+    Bundle { items: Vec<serde_json::Value> },
+    Items(Vec<serde_json::Value>),
+}
+
+/// A line (or bundle entry, counted from 1) that was not imported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportIssue {
+    pub line: usize,
+    pub entry: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub imported: Vec<BlockedItem>,
+    /// Duplicates and rules this app can't express.
+    pub skipped: Vec<ImportIssue>,
+    /// Entries that could not be parsed.
+    pub invalid: Vec<ImportIssue>,
+}
+
+enum Outcome {
+    Item(BlockedItem),
+    Skipped(String),
+    Invalid(String),
+}
+
+struct Entry {
+    line: usize,
+    text: String,
+    outcome: Outcome,
+}
+
+impl Entry {
+    fn new(line: usize, text: &str, outcome: Outcome) -> Self {
+        Entry {
+            line,
+            text: text.trim().to_string(),
+            outcome,
+        }
+    }
+}
+
+fn new_item(
+    name: &str,
+    url_pattern: &str,
+    item_type: BlockItemType,
+    is_active: bool,
+    schedule: Option<BlockSchedule>,
+) -> Outcome {
+    let item = BlockedItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: if name.trim().is_empty() {
+            url_pattern.trim().to_string()
+        } else {
+            name.trim().to_string()
+        },
+        url_pattern: url_pattern.trim().to_string(),
+        item_type,
+        is_active,
+        schedule,
+    };
+    match item.validate() {
+        Ok(()) => Outcome::Item(item),
+        Err(e) => Outcome::Invalid(e),
+    }
+}
+
+fn website(domain: &str) -> Outcome {
+    new_item(domain, domain, BlockItemType::Website, true, None)
+}
+
+fn parse_json(content: &str) -> Result<Vec<Entry>, String> {
+    let values = match serde_json::from_str(content)
+        .map_err(|e| format!("Invalid blocklist JSON: {}", e))?
+    {
+        BundleInput::Bundle { items } | BundleInput::Items(items) => items,
+    };
+
+    Ok(values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let text = value.to_string();
+            let outcome = match serde_json::from_value::<BundleEntry>(value) {
+                Ok(entry) => new_item(
+                    &entry.name,
+                    &entry.url_pattern,
+                    entry.item_type,
+                    entry.is_active,
+                    entry.schedule,
+                ),
+                Err(e) => Outcome::Invalid(e.to_string()),
+            };
+            Entry::new(index + 1, &text, outcome)
+        })
+        .collect())
+}
+
+fn parse_domains(content: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+
+    for (index, raw) in content.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() || line.starts_with('!') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let domains = if fields[0].parse::<IpAddr>().is_ok() {
+            if fields.len() == 1 {
+                entries.push(Entry::new(
+                    index + 1,
+                    raw,
+                    Outcome::Invalid("Hosts line has no hostname".to_string()),
+                ));
+                continue;
+            }
+            &fields[1..]
+        } else if fields.len() > 1 {
+            entries.push(Entry::new(
+                index + 1,
+                raw,
+                Outcome::Invalid("Expected one domain per line".to_string()),
+            ));
+            continue;
+        } else {
+            &fields[..]
+        };
+
+        for domain in domains {
+            let outcome = match *domain {
+                "localhost" | "localhost.localdomain" | "local" | "broadcasthost" => {
+                    Outcome::Skipped("Local hostname".to_string())
+                }
+                domain => website(domain),
+            };
+            entries.push(Entry::new(index + 1, domain, outcome));
+        }
+    }
+
+    entries
+}
+
+fn parse_adblock_rule(line: &str) -> Outcome {
+    if line.starts_with("@@") {
+        return Outcome::Skipped("Exception rules are not supported".to_string());
+    }
+    if line.contains("##") || line.contains("#@#") || line.contains("#?#") {
+        return Outcome::Skipped("Cosmetic rules are not supported".to_string());
+    }
+    let Some(rule) = line.strip_prefix("||") else {
+        return Outcome::Skipped("Only ||domain^ rules are supported".to_string());
+    };
+    if rule.contains('$') {
+        return Outcome::Skipped("Rule options are not supported".to_string());
+    }
+
+    let rule = rule.strip_suffix('^').unwrap_or(rule);
+    if rule.contains('^') || rule.contains('|') {
+        return Outcome::Skipped("Only ||domain^ rules are supported".to_string());
+    }
+
+    let host = rule.split('/').next().unwrap_or_default();
+    if host.is_empty() {
+        return Outcome::Invalid("Rule has no domain".to_string());
+    }
+
+    // `||` covers the domain and its subdomains
+    let pattern = if host.contains('*') {
+        rule.to_string()
+    } else {
+        format!("*.{}", rule)
+    };
+    new_item(host, &pattern, BlockItemType::Website, true, None)
+}
+
+fn parse_adblock(content: &str) -> Vec<Entry> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, raw)| {
+            let line = raw.trim();
+            // Comments and the `[Adblock Plus 2.0]` header
+            if line.is_empty() || line.starts_with('!') || line.starts_with('[') {
+                return None;
+            }
+            Some(Entry::new(index + 1, raw, parse_adblock_rule(line)))
+        })
+        .collect()
+}
+
+/// Identity used to spot duplicates: website patterns are compared in their
+/// canonical form, so `www.reddit.com` and `reddit.com` collide.
+fn item_key(item: &BlockedItem) -> String {
+    match item.item_type {
+        BlockItemType::Website => match UrlPattern::parse(&item.url_pattern) {
+            Ok(pattern) => format!("website:{}", pattern),
+            Err(_) => format!("website:{}", item.url_pattern.trim().to_lowercase()),
+        },
+        BlockItemType::Application => {
+            format!("application:{}", item.url_pattern.trim().to_lowercase())
+        }
+    }
+}
+
+fn website_patterns(items: &[BlockedItem]) -> Vec<UrlPattern> {
+    items
+        .iter()
+        .filter(|item| matches!(item.item_type, BlockItemType::Website))
+        .filter_map(|item| UrlPattern::parse(&item.url_pattern).ok())
+        .collect()
+}
+
+/// Whole-domain website items; wildcard hosts and path rules are left out.
+fn export_domains(items: &[BlockedItem]) -> String {
+    let mut out = String::from("# MindfulDesk blocklist\n");
+    let mut seen = HashSet::new();
+    for pattern in website_patterns(items) {
+        if pattern.path.is_some() {
+            continue;
+        }
+        if let HostRule::Exact(domain) | HostRule::Suffix(domain) = pattern.host {
+            if seen.insert(domain.clone()) {
+                out.push_str(&domain);
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+fn export_adblock(items: &[BlockedItem]) -> String {
+    let mut out = String::from("! MindfulDesk blocklist\n");
+    let mut seen = HashSet::new();
+    for pattern in website_patterns(items) {
+        let host = match &pattern.host {
+            HostRule::Exact(host) | HostRule::Suffix(host) | HostRule::Glob(host) => host,
+            HostRule::Any => continue,
+        };
+        let rule = match &pattern.path {
+            Some(path) => format!("||{}{}", host, path),
+            None => format!("||{}^", host),
+        };
+        if seen.insert(rule.clone()) {
+            out.push_str(&rule);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[tauri::command]
+pub async fn import_blocklist(
+    content: String,
+    format: String,
+    state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
+) -> Result<ImportReport, String> {
+    let entries = match BlocklistFormat::parse(&format)? {
+        BlocklistFormat::Json => parse_json(&content)?,
+        BlocklistFormat::Domains => parse_domains(&content),
+        BlocklistFormat::Adblock => parse_adblock(&content),
+    };
+
+    state.update_settings(&db, |settings| {
+        let mut report = ImportReport::default();
+        let mut seen: HashSet<String> = settings.blocked_items.iter().map(item_key).collect();
+
+        for entry in entries {
+            let issue = |reason: String| ImportIssue {
+                line: entry.line,
+                entry: entry.text.clone(),
+                reason,
+            };
+            match entry.outcome {
+                Outcome::Item(item) if seen.insert(item_key(&item)) => {
+                    settings.blocked_items.push(item.clone());
+                    report.imported.push(item);
+                }
+                Outcome::Item(_) => report
+                    .skipped
+                    .push(issue("Already in the blocklist".to_string())),
+                Outcome::Skipped(reason) => report.skipped.push(issue(reason)),
+                Outcome::Invalid(reason) => report.invalid.push(issue(reason)),
+            }
+        }
+
+        Ok(report)
+    })
+}
+
+/// Exports the blocklist as text. Only JSON carries application items,
+/// schedules and on/off state; the other formats list websites only.
+#[tauri::command]
+pub async fn export_blocklist(
+    format: String,
+    state: State<'_, BlockerManager>,
+) -> Result<String, String> {
+    let items = state.settings().blocked_items;
+
+    match BlocklistFormat::parse(&format)? {
+        BlocklistFormat::Json => serde_json::to_string_pretty(&BlocklistBundle {
+            version: BUNDLE_VERSION,
+            items,
+        })
+        .map_err(|e| e.to_string()),
+        BlocklistFormat::Domains => Ok(export_domains(&items)),
+        BlocklistFormat::Adblock => Ok(export_adblock(&items)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(outcome: &Outcome) -> Option<&str> {
+        match outcome {
+            Outcome::Item(item) => Some(&item.url_pattern),
+            Outcome::Skipped(_) | Outcome::Invalid(_) => None,
+        }
+    }
+
+    #[test]
+    fn adblock_domain_rules_cover_subdomains() {
+        assert_eq!(
+            pattern(&parse_adblock_rule("||reddit.com^")),
+            Some("*.reddit.com")
+        );
+        assert_eq!(
+            pattern(&parse_adblock_rule("||youtube.com/shorts")),
+            Some("*.youtube.com/shorts")
+        );
+        assert_eq!(
+            pattern(&parse_adblock_rule("||*.example.com^")),
+            Some("*.example.com")
+        );
+    }
+
+    #[test]
+    fn adblock_skips_unsupported_rules() {
+        for rule in [
+            "@@||reddit.com^",
+            "reddit.com##.sidebar",
+            "||reddit.com^$third-party",
+            "|https://reddit.com|",
+            "/banner/*",
+        ] {
+            assert!(
+                matches!(parse_adblock_rule(rule), Outcome::Skipped(_)),
+                "{}",
+                rule
+            );
+        }
+        assert!(matches!(parse_adblock_rule("||^"), Outcome::Invalid(_)));
+    }
+
+    #[test]
+    fn adblock_ignores_comments_and_header() {
+        let entries = parse_adblock("[Adblock Plus 2.0]\n! comment\n\n||x.com^\n");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].line, 4);
+        assert_eq!(pattern(&entries[0].outcome), Some("*.x.com"));
+    }
+
+    #[test]
+    fn domains_accept_plain_and_hosts_lines() {
+        let entries = parse_domains(
+            "# blocklist\n\
+             reddit.com\n\
+             0.0.0.0 x.com www.x.com # social\n\
+             127.0.0.1 localhost\n",
+        );
+        let patterns: Vec<_> = entries.iter().map(|e| pattern(&e.outcome)).collect();
+
+        assert_eq!(
+            patterns,
+            vec![Some("reddit.com"), Some("x.com"), Some("www.x.com"), None]
+        );
+        assert_eq!(entries[1].line, 3);
+        assert!(matches!(entries[3].outcome, Outcome::Skipped(_)));
+    }
+
+    #[test]
+    fn domains_reject_malformed_lines() {
+        let entries = parse_domains("reddit.com youtube.com\n0.0.0.0\n");
+        assert_eq!(entries.len(), 2);
+        assert!(entries
+            .iter()
+            .all(|entry| matches!(entry.outcome, Outcome::Invalid(_))));
+    }
+}
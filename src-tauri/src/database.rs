@@ -1,86 +1,371 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use tauri::AppHandle;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use tauri::{AppHandle, Manager};
+
+const DB_FILE_NAME: &str = "mindfuldesk.db";
+
+/// Format version written alongside every stored setting.
+const SETTINGS_VERSION: u32 = 1;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DbError {
     message: String,
 }
 
-pub async fn init_db(_app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    // Database migrations will be handled by Tauri SQL plugin
-    // Schema is defined and will be created on first access
-    /* Migration schema for reference:
-    let _migrations = vec![
-        Migration {
-            version: 1,
-            description: "create initial tables",
-            sql: "
-                -- Journal entries
-                CREATE TABLE IF NOT EXISTS journal_entries (
-                    id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    date TEXT NOT NULL,
-                    mood INTEGER NOT NULL,
-                    notes TEXT NOT NULL,
-                    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-                );
+impl DbError {
+    pub fn new(message: impl Into<String>) -> Self {
+        DbError {
+            message: message.into(),
+        }
+    }
+}
 
-                -- Activity logs
-                CREATE TABLE IF NOT EXISTS activity_logs (
-                    id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    timestamp TEXT NOT NULL,
-                    activity_type TEXT NOT NULL,
-                    duration INTEGER NOT NULL,
-                    metadata TEXT,
-                    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-                );
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
 
-                -- User stats and achievements
-                CREATE TABLE IF NOT EXISTS user_stats (
-                    id INTEGER PRIMARY KEY CHECK (id = 1),
-                    total_focus_sessions INTEGER NOT NULL DEFAULT 0,
-                    current_streak INTEGER NOT NULL DEFAULT 0,
-                    longest_streak INTEGER NOT NULL DEFAULT 0,
-                    total_focus_minutes INTEGER NOT NULL DEFAULT 0,
-                    achievements_unlocked INTEGER NOT NULL DEFAULT 0,
-                    level INTEGER NOT NULL DEFAULT 1,
-                    points INTEGER NOT NULL DEFAULT 0,
-                    last_active_date TEXT,
-                    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-                    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-                );
+impl std::error::Error for DbError {}
 
-                -- Achievements
-                CREATE TABLE IF NOT EXISTS achievements (
-                    id TEXT PRIMARY KEY,
-                    title TEXT NOT NULL,
-                    description TEXT NOT NULL,
-                    icon TEXT NOT NULL,
-                    unlocked INTEGER NOT NULL DEFAULT 0,
-                    unlocked_at TEXT
-                );
+impl From<rusqlite::Error> for DbError {
+    fn from(e: rusqlite::Error) -> Self {
+        DbError::new(format!("Database error: {}", e))
+    }
+}
+
+impl From<std::io::Error> for DbError {
+    fn from(e: std::io::Error) -> Self {
+        DbError::new(format!("I/O error: {}", e))
+    }
+}
+
+impl From<tauri::Error> for DbError {
+    fn from(e: tauri::Error) -> Self {
+        DbError::new(format!("Failed to resolve app data directory: {}", e))
+    }
+}
+
+struct Migration {
+    version: i64,
+    description: &'static str,
+    sql: &'static str,
+}
+
+/// Numbered schema migrations. Append new entries; never edit an applied one.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create initial tables",
+        sql: "
+            -- Journal entries
+            CREATE TABLE IF NOT EXISTS journal_entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                date TEXT NOT NULL,
+                mood INTEGER NOT NULL,
+                notes TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            -- Activity logs
+            CREATE TABLE IF NOT EXISTS activity_logs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                activity_type TEXT NOT NULL,
+                duration INTEGER NOT NULL,
+                metadata TEXT,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            -- User stats and achievements
+            CREATE TABLE IF NOT EXISTS user_stats (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                total_focus_sessions INTEGER NOT NULL DEFAULT 0,
+                current_streak INTEGER NOT NULL DEFAULT 0,
+                longest_streak INTEGER NOT NULL DEFAULT 0,
+                total_focus_minutes INTEGER NOT NULL DEFAULT 0,
+                achievements_unlocked INTEGER NOT NULL DEFAULT 0,
+                level INTEGER NOT NULL DEFAULT 1,
+                points INTEGER NOT NULL DEFAULT 0,
+                last_active_date TEXT,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            -- Achievements
+            CREATE TABLE IF NOT EXISTS achievements (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                description TEXT NOT NULL,
+                icon TEXT NOT NULL,
+                unlocked INTEGER NOT NULL DEFAULT 0,
+                unlocked_at TEXT
+            );
+
+            -- Settings storage
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            -- Initialize default user stats
+            INSERT OR IGNORE INTO user_stats (id) VALUES (1);
+
+            -- Insert default achievements
+            INSERT OR IGNORE INTO achievements (id, title, description, icon) VALUES
+                ('first_session', 'Getting Started', 'Complete your first focus session', '🎯'),
+                ('five_day_streak', 'Consistent Focus', 'Maintain a 5-day streak', '🔥'),
+                ('break_master', 'Break Master', 'Take 20 breaks on time', '☕'),
+                ('deep_work', 'Deep Work', 'Focus for 100+ hours total', '🧠'),
+                ('journal_habit', 'Reflective Mind', 'Journal for 7 consecutive days', '📔'),
+                ('mindful_week', 'Mindful Week', 'Complete all reminders for a week', '🌟');
+        ",
+    },
+    Migration {
+        version: 2,
+        description: "add focus session details to activity logs",
+        sql: "
+            -- Focus sessions store their duration in seconds alongside these details
+            ALTER TABLE activity_logs ADD COLUMN planned_duration INTEGER;
+            ALTER TABLE activity_logs ADD COLUMN pause_count INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE activity_logs ADD COLUMN outcome TEXT;
+
+            CREATE INDEX IF NOT EXISTS idx_activity_logs_timestamp ON activity_logs (timestamp);
+        ",
+    },
+    Migration {
+        version: 3,
+        description: "track journal entry edits",
+        sql: "
+            ALTER TABLE journal_entries ADD COLUMN updated_at TEXT;
+
+            CREATE INDEX IF NOT EXISTS idx_journal_entries_date ON journal_entries (date);
+        ",
+    },
+    Migration {
+        version: 4,
+        description: "create points ledger",
+        sql: "
+            -- Every XP grant, so user_stats.points can be audited
+            CREATE TABLE IF NOT EXISTS points_ledger (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                points INTEGER NOT NULL,
+                reference TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_points_ledger_timestamp ON points_ledger (timestamp);
+        ",
+    },
+    Migration {
+        version: 5,
+        description: "create distraction attempt log",
+        sql: "
+            -- Warned, blocked and overridden visits; item_name is kept in case
+            -- the item is later removed from the blocklist
+            CREATE TABLE IF NOT EXISTS distraction_attempts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                item_id TEXT NOT NULL,
+                item_name TEXT NOT NULL,
+                target TEXT NOT NULL,
+                mode TEXT NOT NULL,
+                overridden INTEGER NOT NULL DEFAULT 0,
+                session_type TEXT
+            );
 
-                -- Settings storage
-                CREATE TABLE IF NOT EXISTS settings (
-                    key TEXT PRIMARY KEY,
-                    value TEXT NOT NULL,
-                    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            CREATE INDEX IF NOT EXISTS idx_distraction_attempts_timestamp
+                ON distraction_attempts (timestamp);
+        ",
+    },
+    Migration {
+        version: 6,
+        description: "create override log",
+        sql: "
+            -- Granted blocker overrides; also counts against the daily budget
+            CREATE TABLE IF NOT EXISTS override_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                duration INTEGER NOT NULL,
+                reason TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_override_log_timestamp ON override_log (timestamp);
+        ",
+    },
+];
+
+/// Envelope for values in the settings table. Settings structs use
+/// `#[serde(default)]`, so data written before a field existed still loads.
+#[derive(Serialize, Deserialize)]
+struct StoredSetting<T> {
+    version: u32,
+    data: T,
+}
+
+/// Shared handle to the app's SQLite database, managed as Tauri state.
+pub struct Database {
+    conn: Mutex<Connection>,
+}
+
+impl Database {
+    pub fn open(path: &Path) -> Result<Self, DbError> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA foreign_keys = ON;",
+        )?;
+
+        Ok(Database {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Locks the connection for the duration of a query or transaction.
+    pub fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap()
+    }
+
+    /// Applies every migration newer than the recorded schema version and
+    /// returns the resulting version.
+    pub fn migrate(&self) -> Result<i64, DbError> {
+        let mut conn = self.conn();
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS schema_version (
+                version INTEGER PRIMARY KEY,
+                description TEXT NOT NULL,
+                applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );",
+        )?;
+
+        let mut current = current_version(&conn)?;
+
+        for migration in MIGRATIONS {
+            if migration.version <= current {
+                continue;
+            }
+
+            let tx = conn.transaction()?;
+            tx.execute_batch(migration.sql).map_err(|e| {
+                DbError::new(format!(
+                    "Migration {} ({}) failed: {}",
+                    migration.version, migration.description, e
+                ))
+            })?;
+            tx.execute(
+                "INSERT INTO schema_version (version, description) VALUES (?1, ?2)",
+                params![migration.version, migration.description],
+            )?;
+            tx.commit()?;
+            current = migration.version;
+        }
+
+        Ok(current)
+    }
+
+    /// Reads a setting saved with `save_setting`. Values that no longer decode
+    /// are moved aside to `<key>.unreadable`, so a later save cannot destroy
+    /// them, and the caller keeps its defaults.
+    pub fn load_setting<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, DbError> {
+        let raw: Option<String> = self
+            .conn()
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        let Some(raw) = raw else {
+            return Ok(None);
+        };
+
+        match serde_json::from_str::<StoredSetting<T>>(&raw) {
+            Ok(stored) => {
+                if stored.version > SETTINGS_VERSION {
+                    eprintln!(
+                        "Setting '{}' was written by a newer version ({}), loading known fields",
+                        key, stored.version
+                    );
+                }
+                Ok(Some(stored.data))
+            }
+            Err(e) => {
+                let backup_key = format!("{}.unreadable", key);
+                eprintln!(
+                    "Moving unreadable setting '{}' to '{}': {}",
+                    key, backup_key, e
                 );
+                let mut conn = self.conn();
+                let tx = conn.transaction()?;
+                tx.execute(
+                    "INSERT OR REPLACE INTO settings (key, value, updated_at)
+                     VALUES (?1, ?2, CURRENT_TIMESTAMP)",
+                    params![backup_key, raw],
+                )?;
+                tx.execute("DELETE FROM settings WHERE key = ?1", params![key])?;
+                tx.commit()?;
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn save_setting<T: Serialize>(&self, key: &str, value: &T) -> Result<(), DbError> {
+        let raw = serde_json::to_string(&StoredSetting {
+            version: SETTINGS_VERSION,
+            data: value,
+        })
+        .map_err(|e| DbError::new(format!("Failed to serialize setting '{}': {}", key, e)))?;
+
+        self.conn().execute(
+            "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, CURRENT_TIMESTAMP)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            params![key, raw],
+        )?;
+        Ok(())
+    }
 
-                -- Initialize default user stats
-                INSERT OR IGNORE INTO user_stats (id) VALUES (1);
-
-                -- Insert default achievements
-                INSERT OR IGNORE INTO achievements (id, title, description, icon) VALUES
-                    ('first_session', 'Getting Started', 'Complete your first focus session', '🎯'),
-                    ('five_day_streak', 'Consistent Focus', 'Maintain a 5-day streak', '🔥'),
-                    ('break_master', 'Break Master', 'Take 20 breaks on time', '☕'),
-                    ('deep_work', 'Deep Work', 'Focus for 100+ hours total', '🧠'),
-                    ('journal_habit', 'Reflective Mind', 'Journal for 7 consecutive days', '📔'),
-                    ('mindful_week', 'Mindful Week', 'Complete all reminders for a week', '🌟');
-            "
-    ];
-    */
-
-    Ok(())
+    /// Applies `change` to a copy of the settings held in `state` and writes
+    /// it through under `key`, leaving `state` untouched if either step fails.
+    pub fn update_setting<T: Clone + Serialize, R>(
+        &self,
+        key: &str,
+        state: &Mutex<T>,
+        change: impl FnOnce(&mut T) -> Result<R, String>,
+    ) -> Result<R, String> {
+        let mut settings = state.lock().unwrap();
+        let mut updated = settings.clone();
+        let result = change(&mut updated)?;
+
+        self.save_setting(key, &updated).map_err(|e| e.to_string())?;
+        *settings = updated;
+        Ok(result)
+    }
+}
+
+fn current_version(conn: &Connection) -> Result<i64, DbError> {
+    let version = conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(version)
 }
 
+/// Opens the database in the app data directory and brings its schema up to date.
+pub fn init_db(app: &AppHandle) -> Result<Database, DbError> {
+    let data_dir = app.path().app_data_dir()?;
+    std::fs::create_dir_all(&data_dir)?;
+
+    let db = Database::open(&data_dir.join(DB_FILE_NAME))?;
+    db.migrate()?;
+
+    Ok(db)
+}
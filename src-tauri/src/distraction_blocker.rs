@@ -1,6 +1,20 @@
+use chrono::{DateTime, Duration, Local, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
-use tauri::State;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::app_blocker::{self, ProcessInfo};
+use crate::block_schedule::BlockSchedule;
+use crate::database::{Database, DbError};
+use crate::distraction_log;
+use crate::focus_manager::{FocusManager, SessionType};
+use crate::override_friction::{OverrideChallenge, OverrideFriction, PendingChallenge};
+use crate::url_matcher::{ParsedUrl, UrlPattern};
+
+pub const OVERRIDE_ENDED_EVENT: &str = "override-ended";
+
+const SETTINGS_KEY: &str = "blocker_settings";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockedItem {
@@ -9,6 +23,9 @@ pub struct BlockedItem {
     pub url_pattern: String,
     pub item_type: BlockItemType,
     pub is_active: bool,
+    /// Limits blocking of this item to certain times; `None` means always.
+    #[serde(default)]
+    pub schedule: Option<BlockSchedule>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,43 +34,525 @@ pub enum BlockItemType {
     Application,
 }
 
+impl BlockedItem {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.url_pattern.trim().is_empty() {
+            return Err("Pattern must not be empty".to_string());
+        }
+        match self.item_type {
+            BlockItemType::Website => {
+                UrlPattern::parse(&self.url_pattern)?;
+            }
+            BlockItemType::Application => app_blocker::validate_pattern(&self.url_pattern)?,
+        }
+        if let Some(schedule) = &self.schedule {
+            schedule.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Whether the item is switched on and inside its schedule at `now`.
+    pub fn is_enforced_at(&self, now: DateTime<Local>) -> bool {
+        self.is_active
+            && self
+                .schedule
+                .as_ref()
+                .is_none_or(|schedule| schedule.is_active_at(now))
+    }
+
+    /// Why `url` matches this item, if it is a website item that does.
+    pub fn match_url(&self, url: &ParsedUrl) -> Option<String> {
+        if !matches!(self.item_type, BlockItemType::Website) {
+            return None;
+        }
+        UrlPattern::parse(&self.url_pattern).ok()?.matches(url)
+    }
+
+    /// Why `process` matches this item, if it is an application item that does.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub fn match_process(&self, process: &ProcessInfo) -> Option<String> {
+        if !matches!(self.item_type, BlockItemType::Application) {
+            return None;
+        }
+        process.matches(&self.url_pattern)
+    }
+
+    /// Stand-in item for a host refused in allowlist mode, so verdicts and the
+    /// attempt log can name the site.
+    fn unlisted(host: &str) -> Self {
+        BlockedItem {
+            id: format!("allowlist:{}", host),
+            name: host.to_string(),
+            url_pattern: host.to_string(),
+            item_type: BlockItemType::Website,
+            is_active: true,
+            schedule: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BlockerSettings {
     pub enabled: bool,
     pub block_mode: BlockMode,
+    pub list_mode: ListMode,
     pub blocked_items: Vec<BlockedItem>,
+    /// Websites that pass in allowlist mode; unused in blocklist mode.
+    #[serde(default)]
+    pub allowed_items: Vec<BlockedItem>,
     pub allow_override: bool,
     pub override_timeout: u32, // seconds
+    /// Hours when blocking applies at all; `None` means always.
+    pub schedule: Option<BlockSchedule>,
+    /// Block during focus sessions and allow everything during breaks,
+    /// regardless of `enabled` and `schedule`.
+    pub enforce_during_focus: bool,
+    /// What Block mode does to a running blocked application.
+    pub app_action: AppBlockAction,
+    pub override_friction: OverrideFriction,
+    /// Overrides allowed per local day; 0 for unlimited.
+    pub daily_override_limit: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BlockMode {
     Warn,    // Show warning but allow access
     Block,   // Completely block access
 }
 
+impl BlockMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockMode::Warn => "warn",
+            BlockMode::Block => "block",
+        }
+    }
+}
+
+/// Which list decides website visits. Application items in `blocked_items`
+/// block in both modes, since allowing only listed processes would stop the
+/// rest of the system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ListMode {
+    Blocklist, // Sites in `blocked_items` are handled per `block_mode`
+    Allowlist, // Only sites in `allowed_items` pass; the rest are handled per `block_mode`
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AppBlockAction {
+    Terminate, // Ask the process to exit
+    Suspend,   // Freeze it until blocking ends
+}
+
+impl BlockerSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        for item in &self.blocked_items {
+            item.validate()?;
+        }
+        for item in &self.allowed_items {
+            if !matches!(item.item_type, BlockItemType::Website) {
+                return Err("Only websites can be allowlisted".to_string());
+            }
+            item.validate()?;
+        }
+        if let Some(schedule) = &self.schedule {
+            schedule.validate()?;
+        }
+        self.override_friction.validate()
+    }
+
+    /// Checks that an override may start now and returns its length: `duration`
+    /// seconds capped at the configured timeout, with 0 asking for the longest.
+    fn override_seconds(&self, db: &Database, duration: u32) -> Result<u32, String> {
+        if !self.allow_override || self.override_timeout == 0 {
+            return Err("Overrides are turned off".to_string());
+        }
+        if self.daily_override_limit > 0 {
+            let used = distraction_log::overrides_today(db).map_err(|e| e.to_string())?;
+            if used >= i64::from(self.daily_override_limit) {
+                return Err(format!(
+                    "Today's override budget of {} is used up",
+                    self.daily_override_limit
+                ));
+            }
+        }
+
+        Ok(if duration == 0 {
+            self.override_timeout
+        } else {
+            duration.min(self.override_timeout)
+        })
+    }
+
+    /// Whether blocking applies at `now` while `session` is running.
+    fn is_enforced_at(&self, now: DateTime<Local>, session: Option<SessionType>) -> bool {
+        if self.enforce_during_focus {
+            match session {
+                Some(SessionType::Focus) => return true,
+                Some(_) => return false,
+                None => {}
+            }
+        }
+
+        self.enabled
+            && self
+                .schedule
+                .as_ref()
+                .is_none_or(|schedule| schedule.is_active_at(now))
+    }
+}
+
+/// First item of `items` enforced at `now` that `matches` accepts, with the reason.
+fn find_match(
+    items: &[BlockedItem],
+    now: DateTime<Local>,
+    matches: impl Fn(&BlockedItem) -> Option<String>,
+) -> Option<(&BlockedItem, String)> {
+    items
+        .iter()
+        .filter(|item| item.is_enforced_at(now))
+        .find_map(|item| matches(item).map(|reason| (item, reason)))
+}
+
+/// What should happen when a URL is visited, as decided by `check_url_blocked`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "verdict", rename_all = "snake_case")]
+pub enum BlockVerdict {
+    Allowed,
+    /// Show a warning but let the visit through.
+    Warn { item: BlockedItem, reason: String },
+    /// Refuse the visit.
+    Blocked { item: BlockedItem, reason: String },
+    /// Would be handled per `mode`, but an override is active until `until`.
+    Overridden {
+        item: BlockedItem,
+        reason: String,
+        mode: BlockMode,
+        until: String,
+    },
+}
+
+impl BlockVerdict {
+    /// The matched item and the mode that applied, unless the visit was allowed.
+    pub fn matched(&self) -> Option<(&BlockedItem, BlockMode, bool)> {
+        match self {
+            BlockVerdict::Allowed => None,
+            BlockVerdict::Warn { item, .. } => Some((item, BlockMode::Warn, false)),
+            BlockVerdict::Blocked { item, .. } => Some((item, BlockMode::Block, false)),
+            BlockVerdict::Overridden { item, mode, .. } => Some((item, *mode, true)),
+        }
+    }
+}
+
 impl Default for BlockerSettings {
     fn default() -> Self {
         BlockerSettings {
             enabled: false,
             block_mode: BlockMode::Warn,
+            list_mode: ListMode::Blocklist,
             blocked_items: vec![],
+            allowed_items: vec![],
             allow_override: true,
             override_timeout: 300, // 5 minutes
+            schedule: None,
+            enforce_during_focus: false,
+            app_action: AppBlockAction::Terminate,
+            override_friction: OverrideFriction::default(),
+            daily_override_limit: 0,
         }
     }
 }
 
+/// Payload of the `override-ended` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverrideEnded {
+    pub expired: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverrideStatus {
+    pub active_until: Option<String>,
+    pub used_today: i64,
+    pub daily_limit: u32,
+    /// `None` when there is no daily limit.
+    pub remaining_today: Option<i64>,
+    pub friction: OverrideFriction,
+}
+
 pub struct BlockerManager {
     settings: Mutex<BlockerSettings>,
-    override_active: Mutex<bool>,
+    override_until: Mutex<Option<DateTime<Utc>>>,
+    override_timer: Mutex<Option<JoinHandle<()>>>,
+    pending_challenge: Mutex<Option<PendingChallenge>>,
 }
 
 impl Default for BlockerManager {
     fn default() -> Self {
         BlockerManager {
             settings: Mutex::new(BlockerSettings::default()),
-            override_active: Mutex::new(false),
+            override_until: Mutex::new(None),
+            override_timer: Mutex::new(None),
+            pending_challenge: Mutex::new(None),
+        }
+    }
+}
+
+impl BlockerManager {
+    pub fn load_settings(&self, db: &Database) -> Result<(), DbError> {
+        if let Some(settings) = db.load_setting(SETTINGS_KEY)? {
+            *self.settings.lock().unwrap() = settings;
+        }
+        Ok(())
+    }
+
+    pub fn settings(&self) -> BlockerSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn update_settings<T>(
+        &self,
+        db: &Database,
+        change: impl FnOnce(&mut BlockerSettings) -> Result<T, String>,
+    ) -> Result<T, String> {
+        db.update_setting(SETTINGS_KEY, &self.settings, change)
+    }
+
+    /// Decides what happens to a visit to `url` at `now`, given the focus
+    /// session currently running.
+    pub fn evaluate(
+        &self,
+        url: &ParsedUrl,
+        now: DateTime<Utc>,
+        session: Option<SessionType>,
+    ) -> BlockVerdict {
+        self.decide(now, session, Some(&url.host), |item| item.match_url(url))
+    }
+
+    /// Like `evaluate`, for a running process.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub fn evaluate_process(
+        &self,
+        process: &ProcessInfo,
+        now: DateTime<Utc>,
+        session: Option<SessionType>,
+    ) -> BlockVerdict {
+        self.decide(now, session, None, |item| item.match_process(process))
+    }
+
+    /// Hostnames to block system-wide at `now`: those of enforced website
+    /// items while Block mode applies and no override is active. A hosts file
+    /// can't express an allowlist, so allowlist mode yields none.
+    pub fn enforced_hosts(&self, now: DateTime<Utc>, session: Option<SessionType>) -> Vec<String> {
+        let settings = self.settings.lock().unwrap();
+        let local_now = now.with_timezone(&Local);
+        if settings.block_mode != BlockMode::Block
+            || settings.list_mode == ListMode::Allowlist
+            || !settings.is_enforced_at(local_now, session)
+            || self.active_override(now).is_some()
+        {
+            return vec![];
+        }
+
+        let mut hosts: Vec<String> = settings
+            .blocked_items
+            .iter()
+            .filter(|item| {
+                item.is_enforced_at(local_now) && matches!(item.item_type, BlockItemType::Website)
+            })
+            .filter_map(|item| UrlPattern::parse(&item.url_pattern).ok())
+            .flat_map(|pattern| pattern.blockable_hosts())
+            .collect();
+        hosts.sort();
+        hosts.dedup();
+        hosts
+    }
+
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub fn app_action(&self) -> AppBlockAction {
+        self.settings.lock().unwrap().app_action
+    }
+
+    /// `host` is the website being visited, if any; in allowlist mode it is
+    /// refused unless an allowed item matches.
+    fn decide(
+        &self,
+        now: DateTime<Utc>,
+        session: Option<SessionType>,
+        host: Option<&str>,
+        matches: impl Fn(&BlockedItem) -> Option<String>,
+    ) -> BlockVerdict {
+        let settings = self.settings.lock().unwrap();
+        let local_now = now.with_timezone(&Local);
+        if !settings.is_enforced_at(local_now, session) {
+            return BlockVerdict::Allowed;
+        }
+
+        let (item, reason) = match (settings.list_mode, host) {
+            (ListMode::Allowlist, Some(host)) => {
+                if find_match(&settings.allowed_items, local_now, matches).is_some() {
+                    return BlockVerdict::Allowed;
+                }
+                (
+                    BlockedItem::unlisted(host),
+                    format!("{} is not on the allowlist", host),
+                )
+            }
+            _ => match find_match(&settings.blocked_items, local_now, matches) {
+                Some((item, reason)) => (item.clone(), reason),
+                None => return BlockVerdict::Allowed,
+            },
+        };
+
+        if let Some(until) = self.active_override(now) {
+            return BlockVerdict::Overridden {
+                item,
+                reason,
+                mode: settings.block_mode,
+                until: until.to_rfc3339(),
+            };
+        }
+
+        match settings.block_mode {
+            BlockMode::Warn => BlockVerdict::Warn { item, reason },
+            BlockMode::Block => BlockVerdict::Blocked { item, reason },
+        }
+    }
+
+    /// Expiry of the current override. An override past its expiry counts as
+    /// ended even if its timer has not fired yet.
+    fn active_override(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.override_until
+            .lock()
+            .unwrap()
+            .filter(|until| *until > now)
+    }
+
+    /// Starts an override of up to `duration` seconds straight away, which is
+    /// only possible when no friction is configured.
+    pub fn request_override(
+        &self,
+        app: &AppHandle,
+        db: &Database,
+        duration: u32,
+    ) -> Result<DateTime<Utc>, String> {
+        let seconds = {
+            let settings = self.settings.lock().unwrap();
+            if settings.override_friction.is_required() {
+                return Err("Complete an override challenge first".to_string());
+            }
+            settings.override_seconds(db, duration)?
+        };
+
+        self.grant_override(app, db, seconds, None)
+    }
+
+    /// Starts a challenge for an override of up to `duration` seconds,
+    /// replacing any challenge already in progress.
+    pub fn begin_override(
+        &self,
+        db: &Database,
+        duration: u32,
+    ) -> Result<OverrideChallenge, String> {
+        let pending = {
+            let settings = self.settings.lock().unwrap();
+            let seconds = settings.override_seconds(db, duration)?;
+            settings
+                .override_friction
+                .new_challenge(seconds, Utc::now())
+        };
+
+        let challenge = pending.challenge.clone();
+        *self.pending_challenge.lock().unwrap() = Some(pending);
+        Ok(challenge)
+    }
+
+    /// Grants the override once the current challenge is answered.
+    pub fn complete_override(
+        &self,
+        app: &AppHandle,
+        db: &Database,
+        challenge_id: &str,
+        phrase: Option<&str>,
+        reason: Option<&str>,
+    ) -> Result<DateTime<Utc>, String> {
+        let seconds = {
+            let mut pending = self.pending_challenge.lock().unwrap();
+            let challenge = pending
+                .as_ref()
+                .ok_or_else(|| "No override challenge in progress".to_string())?;
+            challenge.verify(challenge_id, phrase, reason, Utc::now())?;
+
+            // The budget or timeout may have changed since the challenge began
+            let seconds = self
+                .settings
+                .lock()
+                .unwrap()
+                .override_seconds(db, challenge.challenge.duration)?;
+            *pending = None;
+            seconds
+        };
+
+        self.grant_override(app, db, seconds, reason)
+    }
+
+    pub fn override_status(&self, db: &Database) -> Result<OverrideStatus, String> {
+        let used_today = distraction_log::overrides_today(db).map_err(|e| e.to_string())?;
+        let settings = self.settings.lock().unwrap();
+        let daily_limit = settings.daily_override_limit;
+
+        Ok(OverrideStatus {
+            active_until: self
+                .active_override(Utc::now())
+                .map(|until| until.to_rfc3339()),
+            used_today,
+            daily_limit,
+            remaining_today: (daily_limit > 0)
+                .then(|| (i64::from(daily_limit) - used_today).max(0)),
+            friction: settings.override_friction.clone(),
+        })
+    }
+
+    fn grant_override(
+        &self,
+        app: &AppHandle,
+        db: &Database,
+        seconds: u32,
+        reason: Option<&str>,
+    ) -> Result<DateTime<Utc>, String> {
+        let reason = reason.map(str::trim).filter(|reason| !reason.is_empty());
+        distraction_log::log_override(db, seconds, reason).map_err(|e| e.to_string())?;
+        Ok(self.start_override(app.clone(), seconds))
+    }
+
+    fn start_override(&self, app: AppHandle, seconds: u32) -> DateTime<Utc> {
+        let until = Utc::now() + Duration::seconds(i64::from(seconds));
+        *self.override_until.lock().unwrap() = Some(until);
+
+        let handle = tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(u64::from(seconds))).await;
+            app.state::<BlockerManager>().expire_override(&app);
+        });
+        if let Some(previous) = self.override_timer.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+        until
+    }
+
+    fn expire_override(&self, app: &AppHandle) {
+        let mut override_until = self.override_until.lock().unwrap();
+        if override_until.is_some_and(|until| until <= Utc::now()) {
+            *override_until = None;
+            let _ = app.emit(OVERRIDE_ENDED_EVENT, OverrideEnded { expired: true });
+        }
+    }
+
+    fn end_override(&self, app: &AppHandle) {
+        if let Some(timer) = self.override_timer.lock().unwrap().take() {
+            timer.abort();
+        }
+        if self.override_until.lock().unwrap().take().is_some() {
+            let _ = app.emit(OVERRIDE_ENDED_EVENT, OverrideEnded { expired: false });
         }
     }
 }
@@ -70,10 +569,14 @@ pub async fn get_blocker_settings(
 pub async fn update_blocker_settings(
     settings: BlockerSettings,
     state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
 ) -> Result<(), String> {
-    let mut current_settings = state.settings.lock().unwrap();
-    *current_settings = settings;
-    Ok(())
+    settings.validate()?;
+
+    state.update_settings(&db, |current_settings| {
+        *current_settings = settings;
+        Ok(())
+    })
 }
 
 #[tauri::command]
@@ -82,9 +585,8 @@ pub async fn add_blocked_item(
     url_pattern: String,
     item_type: String,
     state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
 ) -> Result<BlockedItem, String> {
-    let mut settings = state.settings.lock().unwrap();
-    
     let item = BlockedItem {
         id: uuid::Uuid::new_v4().to_string(),
         name,
@@ -94,96 +596,186 @@ pub async fn add_blocked_item(
             _ => BlockItemType::Website,
         },
         is_active: true,
+        schedule: None,
     };
-    
-    settings.blocked_items.push(item.clone());
-    Ok(item)
+    item.validate()?;
+
+    state.update_settings(&db, |settings| {
+        settings.blocked_items.push(item.clone());
+        Ok(item)
+    })
 }
 
 #[tauri::command]
 pub async fn remove_blocked_item(
     id: String,
     state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
 ) -> Result<(), String> {
-    let mut settings = state.settings.lock().unwrap();
-    settings.blocked_items.retain(|item| item.id != id);
-    Ok(())
+    state.update_settings(&db, |settings| {
+        settings.blocked_items.retain(|item| item.id != id);
+        Ok(())
+    })
 }
 
 #[tauri::command]
 pub async fn toggle_blocked_item(
     id: String,
     state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
 ) -> Result<(), String> {
-    let mut settings = state.settings.lock().unwrap();
-    if let Some(item) = settings.blocked_items.iter_mut().find(|i| i.id == id) {
-        item.is_active = !item.is_active;
+    state.update_settings(&db, |settings| {
+        if let Some(item) = settings.blocked_items.iter_mut().find(|i| i.id == id) {
+            item.is_active = !item.is_active;
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn add_allowed_item(
+    name: String,
+    url_pattern: String,
+    state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
+) -> Result<BlockedItem, String> {
+    let item = BlockedItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        url_pattern,
+        item_type: BlockItemType::Website,
+        is_active: true,
+        schedule: None,
+    };
+    item.validate()?;
+
+    state.update_settings(&db, |settings| {
+        settings.allowed_items.push(item.clone());
+        Ok(item)
+    })
+}
+
+#[tauri::command]
+pub async fn remove_allowed_item(
+    id: String,
+    state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    state.update_settings(&db, |settings| {
+        settings.allowed_items.retain(|item| item.id != id);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn toggle_allowed_item(
+    id: String,
+    state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    state.update_settings(&db, |settings| {
+        if let Some(item) = settings.allowed_items.iter_mut().find(|i| i.id == id) {
+            item.is_active = !item.is_active;
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn set_item_schedule(
+    id: String,
+    schedule: Option<BlockSchedule>,
+    state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
+) -> Result<BlockedItem, String> {
+    if let Some(schedule) = &schedule {
+        schedule.validate()?;
     }
-    Ok(())
+
+    state.update_settings(&db, |settings| {
+        let item = settings
+            .blocked_items
+            .iter_mut()
+            .chain(settings.allowed_items.iter_mut())
+            .find(|i| i.id == id)
+            .ok_or_else(|| format!("Item {} not found", id))?;
+        item.schedule = schedule;
+        Ok(item.clone())
+    })
 }
 
 #[tauri::command]
 pub async fn check_url_blocked(
     url: String,
     state: State<'_, BlockerManager>,
-) -> Result<bool, String> {
-    let settings = state.settings.lock().unwrap();
-    let override_active = state.override_active.lock().unwrap();
-    
-    if !settings.enabled || *override_active {
-        return Ok(false);
-    }
-    
-    for item in &settings.blocked_items {
-        if item.is_active && url.contains(&item.url_pattern) {
-            return Ok(true);
-        }
+    focus: State<'_, FocusManager>,
+    db: State<'_, Database>,
+) -> Result<BlockVerdict, String> {
+    let parsed = ParsedUrl::parse(&url)?;
+    let session = focus.active_session();
+    let verdict = state.evaluate(&parsed, Utc::now(), session);
+
+    // A failed log shouldn't change what the caller is told
+    if let Err(e) = distraction_log::log_attempt(&db, &verdict, &url, session) {
+        eprintln!("Failed to log distraction attempt: {}", e);
     }
-    
-    Ok(false)
+    Ok(verdict)
 }
 
+/// Grants an override immediately when no friction is configured. Refusals,
+/// such as a spent daily budget, are returned as errors.
 #[tauri::command]
 pub async fn request_override(
-    _duration: u32,
+    duration: u32,
+    app: AppHandle,
     state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
 ) -> Result<bool, String> {
-    let settings = state.settings.lock().unwrap();
-    
-    if !settings.allow_override {
-        return Ok(false);
-    }
-    
-    let mut override_active = state.override_active.lock().unwrap();
-    *override_active = true;
-    
-    // In a real implementation, you'd spawn a timer to reset this
-    // For now, we'll rely on the frontend to call end_override
-    
+    state.request_override(&app, &db, duration)?;
     Ok(true)
 }
 
 #[tauri::command]
-pub async fn end_override(
+pub async fn begin_override(
+    duration: u32,
     state: State<'_, BlockerManager>,
-) -> Result<(), String> {
-    let mut override_active = state.override_active.lock().unwrap();
-    *override_active = false;
-    Ok(())
+    db: State<'_, Database>,
+) -> Result<OverrideChallenge, String> {
+    state.begin_override(&db, duration)
 }
 
 #[tauri::command]
-pub async fn get_popular_distractions() -> Result<Vec<String>, String> {
-    Ok(vec![
-        "youtube.com".to_string(),
-        "facebook.com".to_string(),
-        "twitter.com".to_string(),
-        "x.com".to_string(),
-        "instagram.com".to_string(),
-        "reddit.com".to_string(),
-        "tiktok.com".to_string(),
-        "netflix.com".to_string(),
-        "twitch.tv".to_string(),
-        "discord.com".to_string(),
-    ])
+pub async fn complete_override(
+    challenge_id: String,
+    phrase: Option<String>,
+    reason: Option<String>,
+    app: AppHandle,
+    state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
+) -> Result<OverrideStatus, String> {
+    state.complete_override(
+        &app,
+        &db,
+        &challenge_id,
+        phrase.as_deref(),
+        reason.as_deref(),
+    )?;
+    state.override_status(&db)
+}
+
+#[tauri::command]
+pub async fn get_override_status(
+    state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
+) -> Result<OverrideStatus, String> {
+    state.override_status(&db)
+}
+
+#[tauri::command]
+pub async fn end_override(
+    app: AppHandle,
+    state: State<'_, BlockerManager>,
+) -> Result<(), String> {
+    state.end_override(&app);
+    Ok(())
 }
@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::database::Database;
+use crate::distraction_blocker::{BlockItemType, BlockedItem, BlockerManager};
+use crate::url_matcher::ParsedUrl;
+
+pub struct CatalogEntry {
+    pub name: &'static str,
+    pub url_pattern: &'static str,
+    /// Offered by `get_popular_distractions` for quick adding.
+    pub popular: bool,
+}
+
+pub struct CatalogCategory {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub entries: &'static [CatalogEntry],
+}
+
+const fn entry(name: &'static str, url_pattern: &'static str, popular: bool) -> CatalogEntry {
+    CatalogEntry {
+        name,
+        url_pattern,
+        popular,
+    }
+}
+
+/// Bundled sites grouped by category. `*.` patterns also cover mobile and
+/// regional subdomains such as m.facebook.com.
+pub const CATALOG: &[CatalogCategory] = &[
+    CatalogCategory {
+        id: "social",
+        name: "Social Media",
+        entries: &[
+            entry("Facebook", "*.facebook.com", true),
+            entry("Instagram", "*.instagram.com", true),
+            entry("Twitter", "*.twitter.com", true),
+            entry("X", "*.x.com", true),
+            entry("TikTok", "*.tiktok.com", true),
+            entry("Reddit", "*.reddit.com", true),
+            entry("Discord", "*.discord.com", true),
+            entry("Snapchat", "*.snapchat.com", false),
+            entry("Pinterest", "*.pinterest.com", false),
+            entry("Threads", "*.threads.net", false),
+            entry("Tumblr", "*.tumblr.com", false),
+            entry("Bluesky", "bsky.app", false),
+        ],
+    },
+    CatalogCategory {
+        id: "video",
+        name: "Video & Streaming",
+        entries: &[
+            entry("YouTube", "*.youtube.com", true),
+            entry("Netflix", "*.netflix.com", true),
+            entry("Twitch", "*.twitch.tv", true),
+            entry("Disney+", "*.disneyplus.com", false),
+            entry("Prime Video", "*.primevideo.com", false),
+            entry("Hulu", "*.hulu.com", false),
+            entry("Vimeo", "*.vimeo.com", false),
+        ],
+    },
+    CatalogCategory {
+        id: "news",
+        name: "News",
+        entries: &[
+            entry("CNN", "*.cnn.com", false),
+            entry("BBC", "*.bbc.com", false),
+            entry("BBC UK", "*.bbc.co.uk", false),
+            entry("The New York Times", "*.nytimes.com", false),
+            entry("The Guardian", "*.theguardian.com", false),
+            entry("Fox News", "*.foxnews.com", false),
+            entry("Reuters", "*.reuters.com", false),
+            entry("Hacker News", "news.ycombinator.com", false),
+        ],
+    },
+    CatalogCategory {
+        id: "gaming",
+        name: "Gaming",
+        entries: &[
+            entry("Steam Store", "store.steampowered.com", false),
+            entry("Steam Community", "steamcommunity.com", false),
+            entry("Epic Games", "*.epicgames.com", false),
+            entry("Roblox", "*.roblox.com", false),
+            entry("itch.io", "*.itch.io", false),
+            entry("Chess.com", "*.chess.com", false),
+            entry("Lichess", "lichess.org", false),
+            entry("IGN", "*.ign.com", false),
+        ],
+    },
+    CatalogCategory {
+        id: "shopping",
+        name: "Shopping",
+        entries: &[
+            entry("Amazon", "*.amazon.com", false),
+            entry("eBay", "*.ebay.com", false),
+            entry("AliExpress", "*.aliexpress.com", false),
+            entry("Etsy", "*.etsy.com", false),
+            entry("Temu", "*.temu.com", false),
+            entry("Walmart", "*.walmart.com", false),
+            entry("Shein", "*.shein.com", false),
+        ],
+    },
+];
+
+impl CatalogEntry {
+    /// The entry's bare domain (plus path), as typed into an address bar.
+    fn domain(&self) -> &'static str {
+        self.url_pattern.trim_start_matches("*.")
+    }
+
+    /// URLs an item must block to cover this entry: the domain, plus a sample
+    /// subdomain for `*.` entries so that an exact `youtube.com` item doesn't
+    /// count as covering `*.youtube.com`.
+    fn samples(&self) -> Vec<ParsedUrl> {
+        let mut samples = vec![self.domain().to_string()];
+        if self.url_pattern.starts_with("*.") {
+            samples.push(format!("m.{}", self.domain()));
+        }
+        samples
+            .iter()
+            .filter_map(|url| ParsedUrl::parse(url).ok())
+            .collect()
+    }
+
+    /// Whether `item` blocks this entry's site at all times when switched on.
+    /// Scheduled items only block part of the time, so they never cover.
+    fn covered_by(&self, item: &BlockedItem, samples: &[ParsedUrl]) -> bool {
+        item.schedule.is_none()
+            && !samples.is_empty()
+            && samples.iter().all(|url| item.match_url(url).is_some())
+    }
+
+    /// Position of the first item covering this entry, switched on or not.
+    fn matching_item(&self, items: &[BlockedItem]) -> Option<usize> {
+        let samples = self.samples();
+        items
+            .iter()
+            .position(|item| self.covered_by(item, &samples))
+    }
+
+    /// The active item that already blocks this entry's site, if any.
+    fn covering_item<'a>(&self, items: &'a [BlockedItem]) -> Option<&'a BlockedItem> {
+        let samples = self.samples();
+        items
+            .iter()
+            .find(|item| item.is_active && self.covered_by(item, &samples))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntryView {
+    pub name: String,
+    pub url_pattern: String,
+    /// Id of the blocked item that already covers this entry.
+    pub covered_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogCategoryView {
+    pub id: String,
+    pub name: String,
+    pub entries: Vec<CatalogEntryView>,
+    pub fully_covered: bool,
+}
+
+fn find_category(id: &str) -> Result<&'static CatalogCategory, String> {
+    CATALOG
+        .iter()
+        .find(|category| category.id == id)
+        .ok_or_else(|| format!("Unknown catalog category '{}'", id))
+}
+
+/// The catalog, marking entries the blocklist already covers.
+#[tauri::command]
+pub async fn get_distraction_catalog(
+    state: State<'_, BlockerManager>,
+) -> Result<Vec<CatalogCategoryView>, String> {
+    let items = state.settings().blocked_items;
+
+    Ok(CATALOG
+        .iter()
+        .map(|category| {
+            let entries: Vec<CatalogEntryView> = category
+                .entries
+                .iter()
+                .map(|entry| CatalogEntryView {
+                    name: entry.name.to_string(),
+                    url_pattern: entry.url_pattern.to_string(),
+                    covered_by: entry.covering_item(&items).map(|item| item.id.clone()),
+                })
+                .collect();
+
+            CatalogCategoryView {
+                id: category.id.to_string(),
+                name: category.name.to_string(),
+                fully_covered: entries.iter().all(|entry| entry.covered_by.is_some()),
+                entries,
+            }
+        })
+        .collect())
+}
+
+/// Blocks every entry of a category not already covered, switching matching
+/// items back on or adding new ones. Returns the items added or re-enabled.
+#[tauri::command]
+pub async fn add_catalog_category(
+    category_id: String,
+    state: State<'_, BlockerManager>,
+    db: State<'_, Database>,
+) -> Result<Vec<BlockedItem>, String> {
+    let category = find_category(&category_id)?;
+
+    state.update_settings(&db, |settings| {
+        let mut added = Vec::new();
+
+        for entry in category.entries {
+            if entry.covering_item(&settings.blocked_items).is_some() {
+                continue;
+            }
+            if let Some(index) = entry.matching_item(&settings.blocked_items) {
+                let item = &mut settings.blocked_items[index];
+                item.is_active = true;
+                added.push(item.clone());
+                continue;
+            }
+
+            let item = BlockedItem {
+                id: uuid::Uuid::new_v4().to_string(),
+                name: entry.name.to_string(),
+                url_pattern: entry.url_pattern.to_string(),
+                item_type: BlockItemType::Website,
+                is_active: true,
+                schedule: None,
+            };
+            settings.blocked_items.push(item.clone());
+            added.push(item);
+        }
+
+        Ok(added)
+    })
+}
+
+/// Bare domains of the catalog's most common distractions, for quick adding.
+#[tauri::command]
+pub async fn get_popular_distractions() -> Result<Vec<String>, String> {
+    Ok(CATALOG
+        .iter()
+        .flat_map(|category| category.entries)
+        .filter(|entry| entry.popular)
+        .map(|entry| entry.domain().to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_schedule::BlockSchedule;
+
+    fn item(url_pattern: &str) -> BlockedItem {
+        BlockedItem {
+            id: url_pattern.to_string(),
+            name: url_pattern.to_string(),
+            url_pattern: url_pattern.to_string(),
+            item_type: BlockItemType::Website,
+            is_active: true,
+            schedule: None,
+        }
+    }
+
+    const YOUTUBE: CatalogEntry = entry("YouTube", "*.youtube.com", true);
+
+    #[test]
+    fn suffix_entries_need_subdomains_covered() {
+        assert!(YOUTUBE.covering_item(&[item("youtube.com")]).is_none());
+        assert!(YOUTUBE.covering_item(&[item("*.youtube.com")]).is_some());
+    }
+
+    #[test]
+    fn exact_entries_are_covered_by_exact_items() {
+        let bluesky = entry("Bluesky", "bsky.app", false);
+        assert!(bluesky.covering_item(&[item("bsky.app")]).is_some());
+        assert!(bluesky.covering_item(&[item("bsky.app/profile")]).is_none());
+    }
+
+    #[test]
+    fn inactive_and_scheduled_items_do_not_cover() {
+        let mut inactive = item("*.youtube.com");
+        inactive.is_active = false;
+        let mut scheduled = item("*.youtube.com");
+        scheduled.schedule = Some(BlockSchedule::default());
+
+        assert!(YOUTUBE.covering_item(&[inactive.clone()]).is_none());
+        assert_eq!(
+            YOUTUBE.matching_item(&[scheduled.clone(), inactive]),
+            Some(1)
+        );
+        assert!(YOUTUBE.covering_item(&[scheduled]).is_none());
+    }
+}
@@ -0,0 +1,213 @@
+use chrono::{Duration, Local, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::database::{Database, DbError};
+use crate::distraction_blocker::BlockVerdict;
+use crate::focus_manager::SessionType;
+use crate::stats_tracker::{local_day_bounds, parse_date};
+
+const MAX_RANGE_DAYS: i64 = 366;
+const DEFAULT_TOP_LIMIT: u32 = 10;
+
+/// Attempts on one blocked item over a period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistractionCount {
+    pub item_id: String,
+    pub item_name: String,
+    pub attempts: i64,
+    pub blocked: i64,
+    pub warned: i64,
+    pub overridden: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistractionStats {
+    pub start_date: String,
+    pub end_date: String,
+    pub total_attempts: i64,
+    pub blocked: i64,
+    pub warned: i64,
+    pub overridden: i64,
+    pub during_focus: i64,
+    pub during_break: i64,
+    pub outside_sessions: i64,
+    /// Share of attempts made while an override was active, from 0 to 1.
+    pub override_rate: f64,
+    pub overrides_granted: i64,
+}
+
+/// Records a warned, blocked or overridden attempt to reach `target`, a URL
+/// or process name. Allowed visits are not logged.
+pub fn log_attempt(
+    db: &Database,
+    verdict: &BlockVerdict,
+    target: &str,
+    session: Option<SessionType>,
+) -> Result<(), DbError> {
+    let Some((item, mode, overridden)) = verdict.matched() else {
+        return Ok(());
+    };
+
+    db.conn().execute(
+        "INSERT INTO distraction_attempts
+             (timestamp, item_id, item_name, target, mode, overridden, session_type)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            Utc::now().to_rfc3339(),
+            item.id,
+            item.name,
+            target,
+            mode.as_str(),
+            overridden,
+            session.map(|session| session.as_str()),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Records a granted override of `duration` seconds and the reason given.
+pub fn log_override(db: &Database, duration: u32, reason: Option<&str>) -> Result<(), DbError> {
+    db.conn().execute(
+        "INSERT INTO override_log (timestamp, duration, reason) VALUES (?1, ?2, ?3)",
+        params![Utc::now().to_rfc3339(), duration, reason],
+    )?;
+    Ok(())
+}
+
+fn count_overrides(conn: &Connection, start: &str, end: &str) -> rusqlite::Result<i64> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM override_log WHERE timestamp >= ?1 AND timestamp < ?2",
+        params![start, end],
+        |row| row.get(0),
+    )
+}
+
+/// Overrides granted so far on the current local day.
+pub fn overrides_today(db: &Database) -> Result<i64, DbError> {
+    let (start, end) = local_day_bounds(Local::now().date_naive());
+    Ok(count_overrides(&db.conn(), &start, &end)?)
+}
+
+/// UTC bounds covering the local days from `start_date` to `end_date` inclusive.
+fn range_bounds(start_date: &str, end_date: &str) -> Result<(String, String), String> {
+    let start = parse_date(start_date)?;
+    let end = parse_date(end_date)?;
+
+    if end < start {
+        return Err("End date must not be before start date".to_string());
+    }
+    if (end - start).num_days() + 1 > MAX_RANGE_DAYS {
+        return Err(format!("Range cannot exceed {} days", MAX_RANGE_DAYS));
+    }
+
+    Ok((local_day_bounds(start).0, local_day_bounds(end).1))
+}
+
+fn top_distractions(
+    conn: &Connection,
+    start: &str,
+    end: &str,
+    limit: u32,
+) -> rusqlite::Result<Vec<DistractionCount>> {
+    let mut stmt = conn.prepare(
+        "SELECT item_id, MAX(item_name), COUNT(*),
+                SUM(CASE WHEN mode = 'block' AND overridden = 0 THEN 1 ELSE 0 END),
+                SUM(CASE WHEN mode = 'warn' AND overridden = 0 THEN 1 ELSE 0 END),
+                SUM(overridden)
+         FROM distraction_attempts
+         WHERE timestamp >= ?1 AND timestamp < ?2
+         GROUP BY item_id
+         ORDER BY COUNT(*) DESC, MAX(item_name)
+         LIMIT ?3",
+    )?;
+
+    let rows = stmt.query_map(params![start, end, limit], |row| {
+        Ok(DistractionCount {
+            item_id: row.get(0)?,
+            item_name: row.get(1)?,
+            attempts: row.get(2)?,
+            blocked: row.get(3)?,
+            warned: row.get(4)?,
+            overridden: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Most attempted items for the local day `date`, or for the week starting
+/// on it when `period` is "week".
+#[tauri::command]
+pub async fn get_top_distractions(
+    period: String,
+    date: String,
+    limit: Option<u32>,
+    db: State<'_, Database>,
+) -> Result<Vec<DistractionCount>, String> {
+    let date = parse_date(&date)?;
+    let days = match period.to_lowercase().as_str() {
+        "day" => 1,
+        "week" => 7,
+        _ => {
+            return Err(format!(
+                "Unsupported period '{}', expected day or week",
+                period
+            ))
+        }
+    };
+
+    let (start, _) = local_day_bounds(date);
+    let (_, end) = local_day_bounds(date + Duration::days(days - 1));
+    top_distractions(&db.conn(), &start, &end, limit.unwrap_or(DEFAULT_TOP_LIMIT))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_distraction_stats(
+    start_date: String,
+    end_date: String,
+    db: State<'_, Database>,
+) -> Result<DistractionStats, String> {
+    let (start, end) = range_bounds(&start_date, &end_date)?;
+
+    let conn = db.conn();
+    let mut stats = conn
+        .query_row(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(CASE WHEN mode = 'block' AND overridden = 0 THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(CASE WHEN mode = 'warn' AND overridden = 0 THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(overridden), 0),
+                    COALESCE(SUM(CASE WHEN session_type = 'focus' THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(CASE WHEN session_type IN ('short_break', 'long_break')
+                                      THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(CASE WHEN session_type IS NULL THEN 1 ELSE 0 END), 0)
+             FROM distraction_attempts
+             WHERE timestamp >= ?1 AND timestamp < ?2",
+            params![start, end],
+            |row| {
+                Ok(DistractionStats {
+                    start_date: start_date.clone(),
+                    end_date: end_date.clone(),
+                    total_attempts: row.get(0)?,
+                    blocked: row.get(1)?,
+                    warned: row.get(2)?,
+                    overridden: row.get(3)?,
+                    during_focus: row.get(4)?,
+                    during_break: row.get(5)?,
+                    outside_sessions: row.get(6)?,
+                    override_rate: 0.0,
+                    overrides_granted: 0,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    stats.overrides_granted = count_overrides(&conn, &start, &end).map_err(|e| e.to_string())?;
+
+    if stats.total_attempts > 0 {
+        let rate = stats.overridden as f64 / stats.total_attempts as f64;
+        stats.override_rate = (rate * 100.0).round() / 100.0;
+    }
+    Ok(stats)
+}
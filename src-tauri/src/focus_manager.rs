@@ -1,8 +1,24 @@
+use chrono::Utc;
+use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
-use tauri::State;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::database::{Database, DbError};
+use crate::gamification::{self, PointsReason};
+
+pub const FOCUS_TICK_EVENT: &str = "focus-tick";
+pub const SESSION_COMPLETED_EVENT: &str = "session-completed";
+
+const SETTINGS_KEY: &str = "focus_settings";
+const MAX_DURATION_MINUTES: u32 = 8 * 60;
+/// Sessions shorter than this are logged but earn no points.
+const MIN_AWARD_SECONDS: u32 = 60;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FocusSettings {
     pub work_duration: u32,      // minutes
     pub short_break: u32,         // minutes
@@ -29,13 +45,82 @@ impl Default for FocusSettings {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl FocusSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, minutes) in [
+            ("Focus duration", self.work_duration),
+            ("Short break", self.short_break),
+            ("Long break", self.long_break),
+        ] {
+            if !(1..=MAX_DURATION_MINUTES).contains(&minutes) {
+                return Err(format!(
+                    "{} must be between 1 and {} minutes",
+                    name, MAX_DURATION_MINUTES
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SessionType {
     Focus,
     ShortBreak,
     LongBreak,
 }
 
+impl SessionType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "focus" => Some(SessionType::Focus),
+            "short_break" => Some(SessionType::ShortBreak),
+            "long_break" => Some(SessionType::LongBreak),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionType::Focus => "focus",
+            SessionType::ShortBreak => "short_break",
+            SessionType::LongBreak => "long_break",
+        }
+    }
+
+    pub fn duration_minutes(&self, settings: &FocusSettings) -> u32 {
+        match self {
+            SessionType::Focus => settings.work_duration,
+            SessionType::ShortBreak => settings.short_break,
+            SessionType::LongBreak => settings.long_break,
+        }
+    }
+
+    /// Pomodoro transition: every focus session is followed by a break, long
+    /// after each `sessions_before_long_break` completed sessions, and every
+    /// break is followed by focus.
+    pub fn next(&self, completed_sessions: u32, settings: &FocusSettings) -> SessionType {
+        match self {
+            SessionType::Focus => {
+                let cycle = settings.sessions_before_long_break.max(1);
+                if completed_sessions > 0 && completed_sessions.is_multiple_of(cycle) {
+                    SessionType::LongBreak
+                } else {
+                    SessionType::ShortBreak
+                }
+            }
+            SessionType::ShortBreak | SessionType::LongBreak => SessionType::Focus,
+        }
+    }
+
+    fn auto_starts(&self, settings: &FocusSettings) -> bool {
+        match self {
+            SessionType::Focus => settings.auto_start_focus,
+            SessionType::ShortBreak | SessionType::LongBreak => settings.auto_start_breaks,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FocusState {
     pub is_active: bool,
@@ -44,6 +129,10 @@ pub struct FocusState {
     pub remaining_seconds: u32,
     pub total_seconds: u32,
     pub completed_sessions: u32,
+    /// Session that follows the current one, or the one to start when idle.
+    pub next_session_type: SessionType,
+    pub started_at: Option<String>,
+    pub pause_count: u32,
 }
 
 impl Default for FocusState {
@@ -55,13 +144,146 @@ impl Default for FocusState {
             remaining_seconds: 0,
             total_seconds: 0,
             completed_sessions: 0,
+            next_session_type: SessionType::Focus,
+            started_at: None,
+            pause_count: 0,
+        }
+    }
+}
+
+impl FocusState {
+    fn begin(&mut self, session_type: SessionType, settings: &FocusSettings) {
+        let seconds = session_type.duration_minutes(settings) * 60;
+        let completed_after = match session_type {
+            SessionType::Focus => self.completed_sessions + 1,
+            _ => self.completed_sessions,
+        };
+
+        self.is_active = true;
+        self.is_paused = false;
+        self.session_type = session_type;
+        self.remaining_seconds = seconds;
+        self.total_seconds = seconds;
+        self.next_session_type = session_type.next(completed_after, settings);
+        self.started_at = Some(Utc::now().to_rfc3339());
+        self.pause_count = 0;
+    }
+
+    /// Snapshot of the running session for the activity log.
+    fn record(&self, outcome: SessionOutcome) -> Option<SessionRecord> {
+        if !self.is_active {
+            return None;
+        }
+
+        Some(SessionRecord {
+            session_type: self.session_type,
+            started_at: self.started_at.clone()?,
+            planned_seconds: self.total_seconds,
+            actual_seconds: self.total_seconds - self.remaining_seconds,
+            pause_count: self.pause_count,
+            outcome,
+        })
+    }
+
+    /// Ends the current session without counting it, keeping the tally.
+    fn reset(&mut self) {
+        *self = FocusState {
+            completed_sessions: self.completed_sessions,
+            ..FocusState::default()
+        };
+    }
+
+    /// Finishes the current session and moves to the next one, starting it
+    /// right away when the matching auto-start flag is set.
+    fn complete(&mut self, settings: &FocusSettings) -> SessionCompleted {
+        let finished = self.session_type;
+        if finished == SessionType::Focus {
+            self.completed_sessions += 1;
+        }
+
+        let next = finished.next(self.completed_sessions, settings);
+        let auto_started = next.auto_starts(settings);
+        if auto_started {
+            self.begin(next, settings);
+        } else {
+            self.is_active = false;
+            self.is_paused = false;
+            self.remaining_seconds = 0;
+            self.next_session_type = next;
+            self.started_at = None;
+        }
+
+        SessionCompleted {
+            finished,
+            next,
+            auto_started,
+            state: self.clone(),
         }
     }
 }
 
+/// Payload of the `session-completed` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionCompleted {
+    pub finished: SessionType,
+    pub next: SessionType,
+    pub auto_started: bool,
+    pub state: FocusState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SessionOutcome {
+    Completed,
+    Abandoned,
+    Interrupted,
+}
+
+impl SessionOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionOutcome::Completed => "completed",
+            SessionOutcome::Abandoned => "abandoned",
+            SessionOutcome::Interrupted => "interrupted",
+        }
+    }
+}
+
+/// A finished, abandoned or interrupted session, as written to activity_logs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_type: SessionType,
+    pub started_at: String,
+    pub planned_seconds: u32,
+    pub actual_seconds: u32,
+    pub pause_count: u32,
+    pub outcome: SessionOutcome,
+}
+
+impl SessionRecord {
+    /// Inserts the record and returns its activity_logs id.
+    fn save(&self, db: &Database) -> Result<i64, DbError> {
+        let conn = db.conn();
+        conn.execute(
+            "INSERT INTO activity_logs
+                 (timestamp, activity_type, duration, planned_duration, pause_count, outcome)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                self.started_at,
+                self.session_type.as_str(),
+                self.actual_seconds,
+                self.planned_seconds,
+                self.pause_count,
+                self.outcome.as_str(),
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+}
+
 pub struct FocusManager {
     pub state: Mutex<FocusState>,
     pub settings: Mutex<FocusSettings>,
+    timer: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl Default for FocusManager {
@@ -69,6 +291,129 @@ impl Default for FocusManager {
         FocusManager {
             state: Mutex::new(FocusState::default()),
             settings: Mutex::new(FocusSettings::default()),
+            timer: Mutex::new(None),
+        }
+    }
+}
+
+enum Tick {
+    Idle,
+    Paused,
+    Running(FocusState),
+    Completed(SessionCompleted, Option<SessionRecord>),
+}
+
+impl FocusManager {
+    pub fn load_settings(&self, db: &Database) -> Result<(), DbError> {
+        if let Some(settings) = db.load_setting::<FocusSettings>(SETTINGS_KEY)? {
+            match settings.validate() {
+                Ok(()) => *self.settings.lock().unwrap() = settings,
+                Err(e) => eprintln!("Ignoring stored focus settings: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Type of the session in progress, paused or not.
+    pub fn active_session(&self) -> Option<SessionType> {
+        let state = self.state.lock().unwrap();
+        state.is_active.then_some(state.session_type)
+    }
+
+    /// Advances the active session by one second.
+    fn tick(&self) -> Tick {
+        let mut state = self.state.lock().unwrap();
+
+        if !state.is_active {
+            return Tick::Idle;
+        }
+        if state.is_paused {
+            return Tick::Paused;
+        }
+
+        state.remaining_seconds = state.remaining_seconds.saturating_sub(1);
+        if state.remaining_seconds > 0 {
+            return Tick::Running(state.clone());
+        }
+
+        let record = state.record(SessionOutcome::Completed);
+        let settings = self.settings.lock().unwrap();
+        Tick::Completed(state.complete(&settings), record)
+    }
+
+    /// Replaces any running countdown with a fresh one for the current session.
+    fn start_timer(&self, app: AppHandle) {
+        self.stop_timer();
+        let handle = tauri::async_runtime::spawn(run_timer(app));
+        *self.timer.lock().unwrap() = Some(handle);
+    }
+
+    fn stop_timer(&self) {
+        if let Some(handle) = self.timer.lock().unwrap().take() {
+            handle.abort();
+        }
+    }
+}
+
+async fn run_timer(app: AppHandle) {
+    let mut interval = tokio::time::interval(Duration::from_secs(1));
+    // The first tick completes immediately
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let manager = app.state::<FocusManager>();
+        match manager.tick() {
+            Tick::Running(state) => {
+                let _ = app.emit(FOCUS_TICK_EVENT, state);
+            }
+            Tick::Paused => {}
+            Tick::Completed(completed, record) => {
+                if let Some(record) = record {
+                    let reason = match record.session_type {
+                        SessionType::Focus => PointsReason::FocusSession,
+                        SessionType::ShortBreak | SessionType::LongBreak => {
+                            PointsReason::OnTimeBreak
+                        }
+                    };
+                    let saved = record.save(&app.state::<Database>()).and_then(|id| {
+                        if record.actual_seconds >= MIN_AWARD_SECONDS {
+                            gamification::award_points(&app, reason, Some(id.to_string()))?;
+                        }
+                        Ok(())
+                    });
+                    if let Err(e) = saved {
+                        eprintln!("Failed to log focus session: {}", e);
+                    }
+                }
+
+                let auto_started = completed.auto_started;
+                let _ = app.emit(SESSION_COMPLETED_EVENT, completed);
+                if !auto_started {
+                    break;
+                }
+            }
+            Tick::Idle => break,
+        }
+    }
+}
+
+/// Logs the running session as interrupted, e.g. when the app exits mid-session.
+pub fn interrupt_active_session(app: &AppHandle) {
+    let manager = app.state::<FocusManager>();
+    manager.stop_timer();
+
+    let record = {
+        let mut state = manager.state.lock().unwrap();
+        let record = state.record(SessionOutcome::Interrupted);
+        state.reset();
+        record
+    };
+
+    if let Some(record) = record {
+        if let Err(e) = record.save(&app.state::<Database>()) {
+            eprintln!("Failed to log focus session: {}", e);
         }
     }
 }
@@ -76,32 +421,29 @@ impl Default for FocusManager {
 #[tauri::command]
 pub fn start_focus_session(
     session_type: String,
+    app: AppHandle,
     manager: State<FocusManager>,
+    db: State<Database>,
 ) -> Result<FocusState, String> {
-    let mut state = manager.state.lock().unwrap();
-    let settings = manager.settings.lock().unwrap();
+    let session_type =
+        SessionType::parse(&session_type).ok_or_else(|| "Invalid session type".to_string())?;
 
-    let duration = match session_type.as_str() {
-        "focus" => settings.work_duration,
-        "short_break" => settings.short_break,
-        "long_break" => settings.long_break,
-        _ => return Err("Invalid session type".to_string()),
-    };
+    let (new_state, interrupted) = {
+        let mut state = manager.state.lock().unwrap();
+        let settings = manager.settings.lock().unwrap();
 
-    let session_enum = match session_type.as_str() {
-        "focus" => SessionType::Focus,
-        "short_break" => SessionType::ShortBreak,
-        "long_break" => SessionType::LongBreak,
-        _ => SessionType::Focus,
+        // Starting over a running session interrupts it
+        let interrupted = state.record(SessionOutcome::Interrupted);
+        state.begin(session_type, &settings);
+        (state.clone(), interrupted)
     };
 
-    state.is_active = true;
-    state.is_paused = false;
-    state.session_type = session_enum;
-    state.remaining_seconds = duration * 60;
-    state.total_seconds = duration * 60;
+    manager.start_timer(app);
 
-    Ok(state.clone())
+    if let Some(record) = interrupted {
+        record.save(&db).map_err(|e| e.to_string())?;
+    }
+    Ok(new_state)
 }
 
 #[tauri::command]
@@ -113,19 +455,31 @@ pub fn pause_focus_session(manager: State<FocusManager>) -> Result<FocusState, S
     }
 
     state.is_paused = !state.is_paused;
+    if state.is_paused {
+        state.pause_count += 1;
+    }
     Ok(state.clone())
 }
 
 #[tauri::command]
-pub fn stop_focus_session(manager: State<FocusManager>) -> Result<FocusState, String> {
-    let mut state = manager.state.lock().unwrap();
-    
-    if matches!(state.session_type, SessionType::Focus) && state.remaining_seconds == 0 {
-        state.completed_sessions += 1;
-    }
+pub fn stop_focus_session(
+    manager: State<FocusManager>,
+    db: State<Database>,
+) -> Result<FocusState, String> {
+    manager.stop_timer();
 
-    *state = FocusState::default();
-    Ok(state.clone())
+    // Completed sessions are counted by the timer; keep the tally across stops
+    let (new_state, abandoned) = {
+        let mut state = manager.state.lock().unwrap();
+        let abandoned = state.record(SessionOutcome::Abandoned);
+        state.reset();
+        (state.clone(), abandoned)
+    };
+
+    if let Some(record) = abandoned {
+        record.save(&db).map_err(|e| e.to_string())?;
+    }
+    Ok(new_state)
 }
 
 #[tauri::command]
@@ -138,8 +492,11 @@ pub fn get_focus_state(manager: State<FocusManager>) -> Result<FocusState, Strin
 pub fn update_focus_settings(
     settings: FocusSettings,
     manager: State<FocusManager>,
+    db: State<Database>,
 ) -> Result<FocusSettings, String> {
+    settings.validate()?;
     let mut current_settings = manager.settings.lock().unwrap();
+    db.save_setting(SETTINGS_KEY, &settings).map_err(|e| e.to_string())?;
     *current_settings = settings.clone();
     Ok(settings)
 }
@@ -1,5 +1,19 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
-use chrono::Utc;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::database::{Database, DbError};
+use crate::health_reminders::REMINDERS_COMPLETED_ACTIVITY;
+
+pub const LEVEL_UP_EVENT: &str = "level-up";
+
+const LEVEL_CURVE_KEY: &str = "level_curve";
+
+/// Highest level the curve is evaluated up to.
+const MAX_LEVEL: i64 = 1000;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Achievement {
@@ -36,102 +50,601 @@ impl Default for UserStats {
     }
 }
 
+/// Points needed for each level: reaching level `n` takes
+/// `base_points * (n - 1) ^ exponent` points in total.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelCurve {
+    pub base_points: i64,
+    pub exponent: f64,
+}
+
+impl Default for LevelCurve {
+    fn default() -> Self {
+        LevelCurve {
+            base_points: 100,
+            exponent: 1.5,
+        }
+    }
+}
+
+impl LevelCurve {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.base_points <= 0 {
+            return Err("Base points must be positive".to_string());
+        }
+        if !self.exponent.is_finite() || self.exponent < 1.0 {
+            return Err("Exponent must be at least 1.0".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn points_for_level(&self, level: i64) -> i64 {
+        if level <= 1 {
+            return 0;
+        }
+        (self.base_points as f64 * ((level - 1) as f64).powf(self.exponent)).round() as i64
+    }
+
+    pub fn level_for(&self, points: i64) -> i64 {
+        let mut level = 1;
+        while level < MAX_LEVEL && self.points_for_level(level + 1) <= points {
+            level += 1;
+        }
+        level
+    }
+}
+
+pub struct GamificationManager {
+    level_curve: Mutex<LevelCurve>,
+}
+
+impl Default for GamificationManager {
+    fn default() -> Self {
+        GamificationManager {
+            level_curve: Mutex::new(LevelCurve::default()),
+        }
+    }
+}
+
+impl GamificationManager {
+    pub fn load_settings(&self, db: &Database) -> Result<(), DbError> {
+        if let Some(curve) = db.load_setting(LEVEL_CURVE_KEY)? {
+            *self.level_curve.lock().unwrap() = curve;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PointsReason {
+    FocusSession,
+    OnTimeBreak,
+    JournalEntry,
+    HonoredReminder,
+}
+
+impl PointsReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PointsReason::FocusSession => "focus_session",
+            PointsReason::OnTimeBreak => "on_time_break",
+            PointsReason::JournalEntry => "journal_entry",
+            PointsReason::HonoredReminder => "honored_reminder",
+        }
+    }
+
+    pub fn points(&self) -> i64 {
+        match self {
+            PointsReason::FocusSession => 20,
+            PointsReason::OnTimeBreak => 5,
+            PointsReason::JournalEntry => 10,
+            PointsReason::HonoredReminder => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointTransaction {
+    pub id: i64,
+    pub timestamp: String,
+    pub reason: String,
+    pub points: i64,
+    pub reference: Option<String>,
+}
+
+/// Payload of the `level-up` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelUp {
+    pub previous_level: i64,
+    pub level: i64,
+    pub points: i64,
+}
+
+/// Records an XP grant in the ledger, re-derives points and level in
+/// user_stats and emits `level-up` when the level increases.
+pub fn award_points(
+    app: &AppHandle,
+    reason: PointsReason,
+    reference: Option<String>,
+) -> Result<PointTransaction, DbError> {
+    let curve = app
+        .state::<GamificationManager>()
+        .level_curve
+        .lock()
+        .unwrap()
+        .clone();
+    let db = app.state::<Database>();
+    let conn = db.conn();
+
+    let timestamp = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO points_ledger (timestamp, reason, points, reference) VALUES (?1, ?2, ?3, ?4)",
+        params![timestamp, reason.as_str(), reason.points(), reference],
+    )?;
+    let transaction = PointTransaction {
+        id: conn.last_insert_rowid(),
+        timestamp,
+        reason: reason.as_str().to_string(),
+        points: reason.points(),
+        reference,
+    };
+
+    let previous_level: i64 = conn.query_row(
+        "SELECT level FROM user_stats WHERE id = 1",
+        [],
+        |row| row.get(0),
+    )?;
+    let (points, level) = sync_level(&conn, &curve)?;
+
+    if level > previous_level {
+        let _ = app.emit(
+            LEVEL_UP_EVENT,
+            LevelUp {
+                previous_level,
+                level,
+                points,
+            },
+        );
+    }
+
+    Ok(transaction)
+}
+
+/// Sets user_stats points to the ledger total and level to match the curve.
+fn sync_level(conn: &Connection, curve: &LevelCurve) -> rusqlite::Result<(i64, i64)> {
+    let points: i64 = conn.query_row(
+        "SELECT COALESCE(SUM(points), 0) FROM points_ledger",
+        [],
+        |row| row.get(0),
+    )?;
+    let level = curve.level_for(points);
+
+    conn.execute(
+        "UPDATE user_stats SET points = ?1, level = ?2, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+        params![points, level],
+    )?;
+    Ok((points, level))
+}
+
+/// What has to be true for an achievement to unlock.
+#[derive(Debug, Clone, Copy)]
+pub enum Condition {
+    FocusSessions(i64),
+    FocusMinutes(i64),
+    StreakDays(i64),
+    CompletedBreaks(i64),
+    JournalDays(i64),
+    ReminderDays(i64),
+}
+
+impl Condition {
+    fn is_met(&self, progress: &Progress) -> bool {
+        match *self {
+            Condition::FocusSessions(n) => progress.stats.total_focus_sessions >= n,
+            Condition::FocusMinutes(n) => progress.stats.total_focus_minutes >= n,
+            Condition::StreakDays(n) => progress.stats.longest_streak >= n,
+            Condition::CompletedBreaks(n) => progress.completed_breaks >= n,
+            Condition::JournalDays(n) => progress.journal_streak >= n,
+            Condition::ReminderDays(n) => progress.reminder_streak >= n,
+        }
+    }
+}
+
+pub struct AchievementDefinition {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub icon: &'static str,
+    pub condition: Condition,
+}
+
+pub const ACHIEVEMENTS: &[AchievementDefinition] = &[
+    AchievementDefinition {
+        id: "first_session",
+        title: "Getting Started",
+        description: "Complete your first focus session",
+        icon: "🎯",
+        condition: Condition::FocusSessions(1),
+    },
+    AchievementDefinition {
+        id: "five_day_streak",
+        title: "Consistent Focus",
+        description: "Maintain a 5-day streak",
+        icon: "🔥",
+        condition: Condition::StreakDays(5),
+    },
+    AchievementDefinition {
+        id: "break_master",
+        title: "Break Master",
+        description: "Take 20 breaks on time",
+        icon: "☕",
+        condition: Condition::CompletedBreaks(20),
+    },
+    AchievementDefinition {
+        id: "deep_work",
+        title: "Deep Work",
+        description: "Focus for 100+ hours total",
+        icon: "🧠",
+        condition: Condition::FocusMinutes(100 * 60),
+    },
+    AchievementDefinition {
+        id: "journal_habit",
+        title: "Reflective Mind",
+        description: "Journal for 7 consecutive days",
+        icon: "📔",
+        condition: Condition::JournalDays(7),
+    },
+    AchievementDefinition {
+        id: "mindful_week",
+        title: "Mindful Week",
+        description: "Complete all reminders for a week",
+        icon: "🌟",
+        condition: Condition::ReminderDays(7),
+    },
+];
+
+/// Everything achievement conditions are evaluated against.
+struct Progress {
+    stats: UserStats,
+    completed_breaks: i64,
+    journal_streak: i64,
+    reminder_streak: i64,
+}
+
+impl Progress {
+    fn load(conn: &Connection) -> rusqlite::Result<Self> {
+        let completed_breaks = conn.query_row(
+            "SELECT COUNT(*) FROM activity_logs
+             WHERE activity_type IN ('short_break', 'long_break') AND outcome = 'completed'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        let journal_dates = {
+            let mut stmt = conn.prepare("SELECT DISTINCT date FROM journal_entries")?;
+            let dates = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            dates
+                .iter()
+                .filter_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+                .collect()
+        };
+
+        let reminder_dates = local_activity_dates(
+            conn,
+            "SELECT timestamp FROM activity_logs WHERE activity_type = ?1",
+            params![REMINDERS_COMPLETED_ACTIVITY],
+        )?;
+
+        Ok(Progress {
+            stats: load_user_stats(conn)?,
+            completed_breaks,
+            journal_streak: longest_run(&journal_dates),
+            reminder_streak: longest_run(&reminder_dates),
+        })
+    }
+}
+
+/// Local calendar dates of the activity_logs timestamps selected by `sql`.
+fn local_activity_dates(
+    conn: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> rusqlite::Result<BTreeSet<NaiveDate>> {
+    let mut stmt = conn.prepare(sql)?;
+    let timestamps = stmt
+        .query_map(params, |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(timestamps
+        .iter()
+        .filter_map(|ts| DateTime::parse_from_rfc3339(ts).ok())
+        .map(|ts| ts.with_timezone(&Local).date_naive())
+        .collect())
+}
+
+/// Length of the longest run of consecutive days.
+fn longest_run(dates: &BTreeSet<NaiveDate>) -> i64 {
+    let mut longest = 0;
+    let mut current = 0;
+    let mut previous: Option<NaiveDate> = None;
+
+    for &date in dates {
+        current = match previous {
+            Some(prev) if prev.succ_opt() == Some(date) => current + 1,
+            _ => 1,
+        };
+        longest = longest.max(current);
+        previous = Some(date);
+    }
+
+    longest
+}
+
+/// Length of the run of consecutive days ending today, or ending yesterday
+/// when today has no activity yet.
+fn current_run(dates: &BTreeSet<NaiveDate>, today: NaiveDate) -> i64 {
+    let mut day = if dates.contains(&today) {
+        today
+    } else {
+        match today.pred_opt() {
+            Some(yesterday) => yesterday,
+            None => return 0,
+        }
+    };
+
+    let mut run = 0;
+    while dates.contains(&day) {
+        run += 1;
+        match day.pred_opt() {
+            Some(prev) => day = prev,
+            None => break,
+        }
+    }
+    run
+}
+
+/// Recomputes streaks from the local dates with at least one completed focus
+/// session and stores them in user_stats.
+pub fn refresh_streak(conn: &Connection, today: NaiveDate) -> rusqlite::Result<UserStats> {
+    let active_dates = local_activity_dates(
+        conn,
+        "SELECT timestamp FROM activity_logs
+         WHERE activity_type = 'focus' AND outcome = 'completed'",
+        [],
+    )?;
+
+    let current_streak = current_run(&active_dates, today);
+    let last_active_date = active_dates
+        .iter()
+        .next_back()
+        .map(|d| d.format("%Y-%m-%d").to_string());
+
+    conn.execute(
+        "UPDATE user_stats
+         SET current_streak = ?1,
+             longest_streak = MAX(longest_streak, ?2),
+             last_active_date = ?3,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+        params![current_streak, longest_run(&active_dates), last_active_date],
+    )?;
+
+    load_user_stats(conn)
+}
+
+/// Totals derived from activity_logs plus the streak and points kept in user_stats.
+pub fn load_user_stats(conn: &Connection) -> rusqlite::Result<UserStats> {
+    let (total_focus_sessions, focus_seconds) = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(duration), 0) FROM activity_logs
+         WHERE activity_type = 'focus' AND outcome = 'completed'",
+        [],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+    )?;
+
+    let achievements_unlocked = conn.query_row(
+        "SELECT COUNT(*) FROM achievements WHERE unlocked = 1",
+        [],
+        |row| row.get(0),
+    )?;
+
+    conn.query_row(
+        "SELECT current_streak, longest_streak, level, points FROM user_stats WHERE id = 1",
+        [],
+        |row| {
+            Ok(UserStats {
+                total_focus_sessions,
+                current_streak: row.get(0)?,
+                longest_streak: row.get(1)?,
+                total_focus_minutes: focus_seconds / 60,
+                achievements_unlocked,
+                level: row.get(2)?,
+                points: row.get(3)?,
+            })
+        },
+    )
+}
+
+fn unlocked_achievements(conn: &Connection) -> rusqlite::Result<HashMap<String, String>> {
+    let mut stmt =
+        conn.prepare("SELECT id, COALESCE(unlocked_at, '') FROM achievements WHERE unlocked = 1")?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+    Ok(rows)
+}
+
+fn to_achievement(definition: &AchievementDefinition, unlocked_at: Option<String>) -> Achievement {
+    Achievement {
+        id: definition.id.to_string(),
+        title: definition.title.to_string(),
+        description: definition.description.to_string(),
+        icon: definition.icon.to_string(),
+        unlocked: unlocked_at.is_some(),
+        unlocked_at,
+    }
+}
+
+/// Evaluates every locked achievement, persists the ones whose condition now
+/// holds and returns exactly those.
+pub fn unlock_achievements(conn: &Connection) -> rusqlite::Result<Vec<Achievement>> {
+    let unlocked = unlocked_achievements(conn)?;
+    let progress = Progress::load(conn)?;
+    let now = Utc::now().to_rfc3339();
+
+    let mut newly_unlocked = Vec::new();
+    for definition in ACHIEVEMENTS {
+        if unlocked.contains_key(definition.id) || !definition.condition.is_met(&progress) {
+            continue;
+        }
+
+        conn.execute(
+            "INSERT INTO achievements (id, title, description, icon, unlocked, unlocked_at)
+             VALUES (?1, ?2, ?3, ?4, 1, ?5)
+             ON CONFLICT(id) DO UPDATE SET unlocked = 1, unlocked_at = excluded.unlocked_at",
+            params![
+                definition.id,
+                definition.title,
+                definition.description,
+                definition.icon,
+                now
+            ],
+        )?;
+        newly_unlocked.push(to_achievement(definition, Some(now.clone())));
+    }
+
+    Ok(newly_unlocked)
+}
+
 #[tauri::command]
 pub async fn update_streak(
-    _app: tauri::AppHandle,
+    db: State<'_, Database>,
 ) -> Result<UserStats, String> {
-    // Mock implementation - in production, calculate streak from database
-    Ok(UserStats {
-        total_focus_sessions: 10,
-        current_streak: 3,
-        longest_streak: 5,
-        total_focus_minutes: 250,
-        achievements_unlocked: 2,
-        level: 2,
-        points: 150,
-    })
+    refresh_streak(&db.conn(), Local::now().date_naive()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn get_achievements(
-    _app: tauri::AppHandle,
+    db: State<'_, Database>,
 ) -> Result<Vec<Achievement>, String> {
-    // Predefined achievements
-    let achievements = vec![
-        Achievement {
-            id: "first_session".to_string(),
-            title: "Getting Started".to_string(),
-            description: "Complete your first focus session".to_string(),
-            icon: "🎯".to_string(),
-            unlocked: true,
-            unlocked_at: Some(Utc::now().to_rfc3339()),
-        },
-        Achievement {
-            id: "five_day_streak".to_string(),
-            title: "Consistent Focus".to_string(),
-            description: "Maintain a 5-day streak".to_string(),
-            icon: "🔥".to_string(),
-            unlocked: false,
-            unlocked_at: None,
-        },
-        Achievement {
-            id: "break_master".to_string(),
-            title: "Break Master".to_string(),
-            description: "Take 20 breaks on time".to_string(),
-            icon: "☕".to_string(),
-            unlocked: false,
-            unlocked_at: None,
-        },
-        Achievement {
-            id: "deep_work".to_string(),
-            title: "Deep Work".to_string(),
-            description: "Focus for 100+ hours total".to_string(),
-            icon: "🧠".to_string(),
-            unlocked: false,
-            unlocked_at: None,
-        },
-        Achievement {
-            id: "journal_habit".to_string(),
-            title: "Reflective Mind".to_string(),
-            description: "Journal for 7 consecutive days".to_string(),
-            icon: "📔".to_string(),
-            unlocked: false,
-            unlocked_at: None,
-        },
-        Achievement {
-            id: "mindful_week".to_string(),
-            title: "Mindful Week".to_string(),
-            description: "Complete all reminders for a week".to_string(),
-            icon: "🌟".to_string(),
-            unlocked: false,
-            unlocked_at: None,
-        },
-    ];
+    let mut unlocked = unlocked_achievements(&db.conn()).map_err(|e| e.to_string())?;
 
-    Ok(achievements)
+    Ok(ACHIEVEMENTS
+        .iter()
+        .map(|definition| to_achievement(definition, unlocked.remove(definition.id)))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_points_history(
+    limit: Option<u32>,
+    db: State<'_, Database>,
+) -> Result<Vec<PointTransaction>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, timestamp, reason, points, reference
+             FROM points_ledger
+             ORDER BY id DESC
+             LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let history = stmt
+        .query_map(params![limit.unwrap_or(100)], |row| {
+            Ok(PointTransaction {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                reason: row.get(2)?,
+                points: row.get(3)?,
+                reference: row.get(4)?,
+            })
+        })
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        .map_err(|e| e.to_string())?;
+
+    Ok(history)
+}
+
+#[tauri::command]
+pub async fn get_level_curve(
+    state: State<'_, GamificationManager>,
+) -> Result<LevelCurve, String> {
+    let curve = state.level_curve.lock().unwrap();
+    Ok(curve.clone())
+}
+
+#[tauri::command]
+pub async fn update_level_curve(
+    curve: LevelCurve,
+    state: State<'_, GamificationManager>,
+    db: State<'_, Database>,
+) -> Result<UserStats, String> {
+    curve.validate()?;
+    db.save_setting(LEVEL_CURVE_KEY, &curve).map_err(|e| e.to_string())?;
+
+    let conn = db.conn();
+    sync_level(&conn, &curve).map_err(|e| e.to_string())?;
+    *state.level_curve.lock().unwrap() = curve;
+
+    load_user_stats(&conn).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn get_user_stats(
-    _app: tauri::AppHandle,
+    db: State<'_, Database>,
 ) -> Result<UserStats, String> {
-    // Mock implementation
-    Ok(UserStats {
-        total_focus_sessions: 10,
-        current_streak: 3,
-        longest_streak: 5,
-        total_focus_minutes: 250,
-        achievements_unlocked: 2,
-        level: 2,
-        points: 150,
-    })
+    load_user_stats(&db.conn()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn check_new_achievements(
-    _app: tauri::AppHandle,
+    db: State<'_, Database>,
 ) -> Result<Vec<Achievement>, String> {
-    // Check if any new achievements were unlocked
-    // Returns list of newly unlocked achievements
-    Ok(vec![])
+    let conn = db.conn();
+    // Streak achievements need an up-to-date streak
+    refresh_streak(&conn, Local::now().date_naive()).map_err(|e| e.to_string())?;
+    unlock_achievements(&conn).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    fn dates(days: &[u32]) -> BTreeSet<NaiveDate> {
+        days.iter().map(|&d| day(d)).collect()
+    }
+
+    #[test]
+    fn longest_run_finds_the_longest_streak() {
+        assert_eq!(longest_run(&dates(&[])), 0);
+        assert_eq!(longest_run(&dates(&[5])), 1);
+        assert_eq!(longest_run(&dates(&[1, 2, 3, 5, 6])), 3);
+        assert_eq!(longest_run(&dates(&[1, 3, 4, 5, 6, 9])), 4);
+    }
+
+    #[test]
+    fn longest_run_spans_month_ends() {
+        let mut dates = dates(&[1, 2]);
+        dates.insert(NaiveDate::from_ymd_opt(2026, 9, 30).unwrap());
+        assert_eq!(longest_run(&dates), 3);
+    }
+
+    #[test]
+    fn current_run_ends_today_or_yesterday() {
+        let history = dates(&[10, 11, 12, 14, 15]);
+
+        assert_eq!(current_run(&history, day(15)), 2);
+        // Today not logged yet: the streak through yesterday still counts
+        assert_eq!(current_run(&history, day(16)), 2);
+        assert_eq!(current_run(&history, day(17)), 0);
+        assert_eq!(current_run(&history, day(12)), 3);
+        assert_eq!(current_run(&dates(&[]), day(12)), 0);
+    }
 }
@@ -1,6 +1,22 @@
+use chrono::{DateTime, Duration, Local, Utc};
+use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{AppHandle, State};
+
+use crate::database::{Database, DbError};
+use crate::gamification::{self, PointsReason, PointTransaction};
+use crate::stats_tracker::local_day_bounds;
+
+/// activity_logs type for a reminder the user acted on.
+pub const REMINDER_HONORED_ACTIVITY: &str = "reminder_honored";
+
+/// activity_logs type marking a local day on which every enabled reminder
+/// was honored.
+pub const REMINDERS_COMPLETED_ACTIVITY: &str = "reminders_completed";
+
+const SETTINGS_KEY: &str = "reminder_settings";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthReminder {
@@ -34,6 +50,7 @@ impl ToString for ReminderType {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ReminderSettings {
     pub reminders: Vec<HealthReminder>,
     pub enabled: bool,
@@ -95,6 +112,23 @@ impl Default for ReminderManager {
     }
 }
 
+impl ReminderManager {
+    pub fn load_settings(&self, db: &Database) -> Result<(), DbError> {
+        if let Some(settings) = db.load_setting(SETTINGS_KEY)? {
+            *self.settings.lock().unwrap() = settings;
+        }
+        Ok(())
+    }
+
+    fn update_settings<T>(
+        &self,
+        db: &Database,
+        change: impl FnOnce(&mut ReminderSettings) -> Result<T, String>,
+    ) -> Result<T, String> {
+        db.update_setting(SETTINGS_KEY, &self.settings, change)
+    }
+}
+
 #[tauri::command]
 pub async fn get_reminder_settings(
     state: State<'_, ReminderManager>,
@@ -107,10 +141,12 @@ pub async fn get_reminder_settings(
 pub async fn update_reminder_settings(
     settings: ReminderSettings,
     state: State<'_, ReminderManager>,
+    db: State<'_, Database>,
 ) -> Result<(), String> {
-    let mut current_settings = state.settings.lock().unwrap();
-    *current_settings = settings;
-    Ok(())
+    state.update_settings(&db, |current_settings| {
+        *current_settings = settings;
+        Ok(())
+    })
 }
 
 #[tauri::command]
@@ -118,9 +154,8 @@ pub async fn add_custom_reminder(
     interval_minutes: u32,
     message: String,
     state: State<'_, ReminderManager>,
+    db: State<'_, Database>,
 ) -> Result<HealthReminder, String> {
-    let mut settings = state.settings.lock().unwrap();
-    
     let reminder = HealthReminder {
         id: uuid::Uuid::new_v4().to_string(),
         reminder_type: ReminderType::Custom,
@@ -129,9 +164,11 @@ pub async fn add_custom_reminder(
         enabled: true,
         last_triggered: None,
     };
-    
-    settings.reminders.push(reminder.clone());
-    Ok(reminder)
+
+    state.update_settings(&db, |settings| {
+        settings.reminders.push(reminder.clone());
+        Ok(reminder)
+    })
 }
 
 #[tauri::command]
@@ -141,48 +178,163 @@ pub async fn update_reminder(
     message: Option<String>,
     enabled: Option<bool>,
     state: State<'_, ReminderManager>,
+    db: State<'_, Database>,
 ) -> Result<(), String> {
-    let mut settings = state.settings.lock().unwrap();
-    
-    if let Some(reminder) = settings.reminders.iter_mut().find(|r| r.id == id) {
-        if let Some(interval) = interval_minutes {
-            reminder.interval_minutes = interval;
+    state.update_settings(&db, |settings| {
+        if let Some(reminder) = settings.reminders.iter_mut().find(|r| r.id == id) {
+            if let Some(interval) = interval_minutes {
+                reminder.interval_minutes = interval;
+            }
+            if let Some(msg) = message {
+                reminder.message = msg;
+            }
+            if let Some(en) = enabled {
+                reminder.enabled = en;
+            }
+            Ok(())
+        } else {
+            Err("Reminder not found".to_string())
         }
-        if let Some(msg) = message {
-            reminder.message = msg;
-        }
-        if let Some(en) = enabled {
-            reminder.enabled = en;
-        }
-        Ok(())
-    } else {
-        Err("Reminder not found".to_string())
-    }
+    })
 }
 
 #[tauri::command]
 pub async fn delete_reminder(
     id: String,
     state: State<'_, ReminderManager>,
+    db: State<'_, Database>,
 ) -> Result<(), String> {
-    let mut settings = state.settings.lock().unwrap();
-    settings.reminders.retain(|r| r.id != id);
-    Ok(())
+    state.update_settings(&db, |settings| {
+        settings.reminders.retain(|r| r.id != id);
+        Ok(())
+    })
 }
 
 #[tauri::command]
 pub async fn toggle_reminder(
     id: String,
     state: State<'_, ReminderManager>,
+    db: State<'_, Database>,
 ) -> Result<(), String> {
-    let mut settings = state.settings.lock().unwrap();
-    
-    if let Some(reminder) = settings.reminders.iter_mut().find(|r| r.id == id) {
-        reminder.enabled = !reminder.enabled;
-        Ok(())
-    } else {
-        Err("Reminder not found".to_string())
+    state.update_settings(&db, |settings| {
+        if let Some(reminder) = settings.reminders.iter_mut().find(|r| r.id == id) {
+            reminder.enabled = !reminder.enabled;
+            Ok(())
+        } else {
+            Err("Reminder not found".to_string())
+        }
+    })
+}
+
+/// When `reminder_id` was last honored, from its reminder_honored rows.
+fn last_honored(db: &Database, reminder_id: &str) -> Result<Option<DateTime<Utc>>, DbError> {
+    let timestamp: Option<String> = db.conn().query_row(
+        "SELECT MAX(timestamp) FROM activity_logs
+         WHERE activity_type = ?1 AND metadata = ?2",
+        params![REMINDER_HONORED_ACTIVITY, reminder_id],
+        |row| row.get(0),
+    )?;
+
+    Ok(timestamp
+        .and_then(|ts| DateTime::parse_from_rfc3339(&ts).ok())
+        .map(|ts| ts.with_timezone(&Utc)))
+}
+
+/// Logs a reminders_completed row the first time on the current local day
+/// that every reminder in `enabled_ids` has been honored.
+fn record_day_completion(db: &Database, enabled_ids: &[String]) -> Result<(), DbError> {
+    let (start, end) = local_day_bounds(Local::now().date_naive());
+    let conn = db.conn();
+
+    let completed: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM activity_logs
+         WHERE activity_type = ?1 AND timestamp >= ?2 AND timestamp < ?3",
+        params![REMINDERS_COMPLETED_ACTIVITY, start, end],
+        |row| row.get(0),
+    )?;
+    if completed > 0 {
+        return Ok(());
     }
+
+    let honored: HashSet<String> = {
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT metadata FROM activity_logs
+             WHERE activity_type = ?1 AND timestamp >= ?2 AND timestamp < ?3
+               AND metadata IS NOT NULL",
+        )?;
+        let ids = stmt
+            .query_map(params![REMINDER_HONORED_ACTIVITY, start, end], |row| {
+                row.get(0)
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        ids
+    };
+    if !enabled_ids.iter().all(|id| honored.contains(id)) {
+        return Ok(());
+    }
+
+    conn.execute(
+        "INSERT INTO activity_logs (timestamp, activity_type, duration) VALUES (?1, ?2, 0)",
+        params![Utc::now().to_rfc3339(), REMINDERS_COMPLETED_ACTIVITY],
+    )?;
+    Ok(())
+}
+
+/// Records that the user acted on a reminder and awards points for it. Each
+/// enabled reminder can be honored once per `interval_minutes`.
+#[tauri::command]
+pub async fn honor_reminder(
+    id: String,
+    app: AppHandle,
+    state: State<'_, ReminderManager>,
+    db: State<'_, Database>,
+) -> Result<PointTransaction, String> {
+    let (reminder, enabled_ids) = {
+        let settings = state.settings.lock().unwrap();
+        let reminder = settings
+            .reminders
+            .iter()
+            .find(|r| r.id == id)
+            .ok_or_else(|| "Reminder not found".to_string())?;
+        if !settings.enabled || !reminder.enabled {
+            return Err("Reminder is disabled".to_string());
+        }
+        let enabled_ids: Vec<String> = settings
+            .reminders
+            .iter()
+            .filter(|r| r.enabled)
+            .map(|r| r.id.clone())
+            .collect();
+        (reminder.clone(), enabled_ids)
+    };
+
+    let now = Utc::now();
+    if let Some(last) = last_honored(&db, &id).map_err(|e| e.to_string())? {
+        let next = last + Duration::minutes(i64::from(reminder.interval_minutes));
+        if now < next {
+            return Err(format!(
+                "Reminder was already honored; it can be honored again after {}",
+                next.to_rfc3339()
+            ));
+        }
+    }
+
+    // The reminder id goes in metadata so the cooldown can find this row
+    db.conn()
+        .execute(
+            "INSERT INTO activity_logs (timestamp, activity_type, duration, metadata)
+             VALUES (?1, ?2, 0, ?3)",
+            params![now.to_rfc3339(), REMINDER_HONORED_ACTIVITY, id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    // The honor is saved, so a failure here must not fail the command
+    if let Err(e) = record_day_completion(&db, &enabled_ids) {
+        eprintln!("Failed to record completed reminders: {}", e);
+    }
+
+    gamification::award_points(&app, PointsReason::HonoredReminder, Some(id))
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -0,0 +1,500 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::database::{Database, DbError};
+use crate::distraction_blocker::BlockerManager;
+use crate::focus_manager::FocusManager;
+
+const SETTINGS_KEY: &str = "hosts_blocker_settings";
+
+pub const SECTION_BEGIN: &str = "# BEGIN MindfulDesk blocklist";
+pub const SECTION_END: &str = "# END MindfulDesk blocklist";
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HostsSettings {
+    pub enabled: bool,
+    pub hosts_path: PathBuf,
+}
+
+impl Default for HostsSettings {
+    fn default() -> Self {
+        HostsSettings {
+            enabled: false,
+            hosts_path: default_hosts_path(),
+        }
+    }
+}
+
+fn default_hosts_path() -> PathBuf {
+    if cfg!(target_os = "windows") {
+        PathBuf::from(r"C:\Windows\System32\drivers\etc\hosts")
+    } else {
+        PathBuf::from("/etc/hosts")
+    }
+}
+
+/// Result of `get_hosts_blocker_status`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostsStatus {
+    pub enabled: bool,
+    pub hosts_path: PathBuf,
+    /// Hostnames currently in the managed section.
+    pub blocked_hosts: Vec<String>,
+    /// Why the last write failed, typically missing permissions.
+    pub last_error: Option<String>,
+}
+
+/// Mirrors blocked websites into a delimited section of the hosts file while
+/// Block mode applies, so they are blocked system-wide.
+pub struct HostsBlocker {
+    settings: Mutex<HostsSettings>,
+    /// Hosts last written, or `None` if the file hasn't been synced yet.
+    /// Held while writing so the background sync and commands don't interleave.
+    applied: Mutex<Option<Vec<String>>>,
+    last_error: Mutex<Option<String>>,
+}
+
+impl Default for HostsBlocker {
+    fn default() -> Self {
+        HostsBlocker {
+            settings: Mutex::new(HostsSettings::default()),
+            applied: Mutex::new(None),
+            last_error: Mutex::new(None),
+        }
+    }
+}
+
+impl HostsBlocker {
+    pub fn load_settings(&self, db: &Database) -> Result<(), DbError> {
+        if let Some(settings) = db.load_setting(SETTINGS_KEY)? {
+            *self.settings.lock().unwrap() = settings;
+        }
+        Ok(())
+    }
+
+    /// Brings the hosts file in line with the blocker, writing only on change.
+    fn sync(&self, app: &AppHandle) {
+        let mut applied = self.applied.lock().unwrap();
+        let settings = self.settings.lock().unwrap().clone();
+        if !settings.enabled {
+            return;
+        }
+
+        let hosts = current_hosts(app);
+        if applied.as_ref() == Some(&hosts) {
+            return;
+        }
+
+        match write_section(&settings.hosts_path, &hosts) {
+            Ok(()) => {
+                *applied = Some(hosts);
+                *self.last_error.lock().unwrap() = None;
+            }
+            Err(e) => {
+                // Retried on every sync; only report each new failure once
+                let mut last_error = self.last_error.lock().unwrap();
+                if last_error.as_ref() != Some(&e) {
+                    eprintln!("{}", e);
+                }
+                *last_error = Some(e);
+            }
+        }
+    }
+}
+
+fn current_hosts(app: &AppHandle) -> Vec<String> {
+    let session = app.state::<FocusManager>().active_session();
+    app.state::<BlockerManager>()
+        .enforced_hosts(Utc::now(), session)
+}
+
+/// Keeps the hosts file in sync in the background. File writes block, so this
+/// runs on its own thread like the app watcher.
+pub fn start_sync(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        app.state::<HostsBlocker>().sync(&app);
+        std::thread::sleep(SYNC_INTERVAL);
+    });
+}
+
+/// Removes the managed section, e.g. when the app exits.
+pub fn clear(app: &AppHandle) {
+    let blocker = app.state::<HostsBlocker>();
+    let mut applied = blocker.applied.lock().unwrap();
+    let settings = blocker.settings.lock().unwrap().clone();
+    if !settings.enabled {
+        return;
+    }
+
+    match write_section(&settings.hosts_path, &[]) {
+        Ok(()) => *applied = Some(vec![]),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+/// Replaces the managed section of the hosts file at `path` with entries for
+/// `hosts`, removing it when `hosts` is empty. Nothing outside the section is
+/// changed, and the file is left alone if it already matches.
+pub fn write_section(path: &Path, hosts: &[String]) -> Result<(), String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let updated =
+        replace_section(&contents, hosts).map_err(|e| format!("{} in {}", e, path.display()))?;
+
+    if updated != contents {
+        write_file(path, &updated)?;
+    }
+    Ok(())
+}
+
+/// Replaces the file at `path` without ever leaving it half-written: the new
+/// contents go to a temporary file beside it, which is synced and renamed
+/// over the original. Where that isn't possible, such as a bind-mounted hosts
+/// file or a directory the user can't write to, the file is backed up to the
+/// temp directory and rewritten in place.
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    // Renaming over a symlink would replace the link rather than its target
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp = path.with_file_name(format!(".{}.mindfuldesk-tmp", file_name));
+
+    match replace_with(&path, &temp, contents) {
+        Ok(()) => Ok(()),
+        Err(_) => {
+            let _ = std::fs::remove_file(&temp);
+            write_in_place(&path, &file_name, contents)
+        }
+    }
+}
+
+fn replace_with(path: &Path, temp: &Path, contents: &str) -> std::io::Result<()> {
+    let permissions = std::fs::metadata(path)?.permissions();
+    let mut file = std::fs::File::create(temp)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    std::fs::set_permissions(temp, permissions)?;
+    std::fs::rename(temp, path)?;
+
+    // Persist the rename itself; not every platform can sync a directory
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = std::fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+fn write_in_place(path: &Path, file_name: &str, contents: &str) -> Result<(), String> {
+    let backup = back_up(path, file_name)
+        .map_err(|e| format!("Failed to back up {} before writing: {}", path.display(), e))?;
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(path)
+        .and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        .map_err(|e| {
+            format!(
+                "Failed to write {}: {}; the previous contents are in {}",
+                path.display(),
+                e,
+                backup.display()
+            )
+        })
+}
+
+/// Copies `path` to a new, uniquely named file in the temp directory. The
+/// temp directory is usually world-writable and this may run as root, so the
+/// backup is created exclusively: an existing file or symlink at the chosen
+/// name is never written through.
+fn back_up(path: &Path, file_name: &str) -> std::io::Result<PathBuf> {
+    let backup = std::env::temp_dir().join(format!(
+        "{}.mindfuldesk-backup-{}",
+        file_name,
+        uuid::Uuid::new_v4().simple()
+    ));
+    let mut source = std::fs::File::open(path)?;
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&backup)?;
+    std::io::copy(&mut source, &mut file)?;
+    file.sync_all()?;
+    Ok(backup)
+}
+
+/// Byte range of the managed section, including both marker lines.
+fn find_section(contents: &str) -> Result<Option<Range<usize>>, String> {
+    let mut begin = None;
+    let mut offset = 0;
+
+    for line in contents.split_inclusive('\n') {
+        match line.trim() {
+            SECTION_BEGIN if begin.is_some() => {
+                return Err("Nested MindfulDesk section".to_string());
+            }
+            SECTION_BEGIN => begin = Some(offset),
+            SECTION_END => {
+                let Some(start) = begin else {
+                    return Err("MindfulDesk section end without a start".to_string());
+                };
+                return Ok(Some(start..offset + line.len()));
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+
+    match begin {
+        // Refuse to guess where a damaged section ends rather than delete the rest
+        Some(_) => Err("Unterminated MindfulDesk section".to_string()),
+        None => Ok(None),
+    }
+}
+
+fn replace_section(contents: &str, hosts: &[String]) -> Result<String, String> {
+    let newline = if contents.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let (before, after) = match find_section(contents)? {
+        Some(range) => {
+            let (before, after) = (&contents[..range.start], &contents[range.end..]);
+            // A section that ends the file unterminated was appended to a
+            // file without a final newline; the line break before it is ours
+            if contents[..range.end].ends_with('\n') {
+                (before, after)
+            } else {
+                let before = before
+                    .strip_suffix("\r\n")
+                    .or_else(|| before.strip_suffix('\n'))
+                    .unwrap_or(before);
+                (before, after)
+            }
+        }
+        None => (contents, ""),
+    };
+
+    let mut updated = before.to_string();
+    if !hosts.is_empty() {
+        let section = render_section(hosts, newline);
+        if updated.is_empty() || updated.ends_with('\n') {
+            updated.push_str(&section);
+        } else {
+            // Keep the separating line break inside the section, leaving the
+            // file unterminated as before, so removing it restores every byte
+            updated.push_str(newline);
+            updated.push_str(section.strip_suffix(newline).unwrap_or(&section));
+        }
+    }
+    updated.push_str(after);
+    Ok(updated)
+}
+
+fn render_section(hosts: &[String], newline: &str) -> String {
+    let mut lines = vec![
+        SECTION_BEGIN.to_string(),
+        "# Managed by MindfulDesk; edits inside this section are overwritten".to_string(),
+    ];
+    for host in hosts {
+        lines.push(format!("0.0.0.0 {}", host));
+        lines.push(format!(":: {}", host));
+    }
+    lines.push(SECTION_END.to_string());
+
+    let mut section = lines.join(newline);
+    section.push_str(newline);
+    section
+}
+
+#[tauri::command]
+pub async fn get_hosts_blocker_settings(
+    state: State<'_, HostsBlocker>,
+) -> Result<HostsSettings, String> {
+    let settings = state.settings.lock().unwrap();
+    Ok(settings.clone())
+}
+
+/// Applies the new settings to the hosts file before saving them, so a file
+/// that can't be written is reported instead of failing silently later.
+#[tauri::command]
+pub async fn update_hosts_blocker_settings(
+    settings: HostsSettings,
+    app: AppHandle,
+    state: State<'_, HostsBlocker>,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    if settings.hosts_path.as_os_str().is_empty() {
+        return Err("Hosts file path must not be empty".to_string());
+    }
+
+    let mut applied = state.applied.lock().unwrap();
+    let previous = state.settings.lock().unwrap().clone();
+    let moved = previous.hosts_path != settings.hosts_path;
+
+    let mut written = None;
+    if settings.enabled {
+        let hosts = current_hosts(&app);
+        write_section(&settings.hosts_path, &hosts)?;
+        written = Some(hosts);
+    }
+    if previous.enabled && (!settings.enabled || moved) {
+        if let Err(e) = write_section(&previous.hosts_path, &[]) {
+            // Keep the previous settings in force: undo the write to the new
+            // path and have the next sync rewrite the old one from scratch
+            if settings.enabled && moved {
+                let _ = write_section(&settings.hosts_path, &[]);
+            }
+            *applied = None;
+            return Err(e);
+        }
+    }
+    *applied = written;
+
+    db.save_setting(SETTINGS_KEY, &settings)
+        .map_err(|e| e.to_string())?;
+    *state.settings.lock().unwrap() = settings;
+    *state.last_error.lock().unwrap() = None;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_hosts_blocker_status(
+    state: State<'_, HostsBlocker>,
+) -> Result<HostsStatus, String> {
+    let blocked_hosts = state.applied.lock().unwrap().clone().unwrap_or_default();
+    let settings = state.settings.lock().unwrap().clone();
+    Ok(HostsStatus {
+        enabled: settings.enabled,
+        hosts_path: settings.hosts_path,
+        blocked_hosts,
+        last_error: state.last_error.lock().unwrap().clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hosts(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    /// A scratch hosts file holding `contents`, removed when dropped.
+    struct TempHosts(PathBuf);
+
+    impl TempHosts {
+        fn new(contents: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("hosts-test-{}", uuid::Uuid::new_v4()));
+            std::fs::write(&path, contents).unwrap();
+            TempHosts(path)
+        }
+
+        fn read(&self) -> String {
+            std::fs::read_to_string(&self.0).unwrap()
+        }
+    }
+
+    impl Drop for TempHosts {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn appends_section_when_missing() {
+        let file = TempHosts::new("127.0.0.1 localhost\n");
+        write_section(&file.0, &hosts(&["reddit.com"])).unwrap();
+
+        assert_eq!(
+            file.read(),
+            format!(
+                "127.0.0.1 localhost\n{}\n\
+                 # Managed by MindfulDesk; edits inside this section are overwritten\n\
+                 0.0.0.0 reddit.com\n:: reddit.com\n{}\n",
+                SECTION_BEGIN, SECTION_END
+            )
+        );
+    }
+
+    #[test]
+    fn round_trips_file_without_final_newline() {
+        let original = "127.0.0.1 localhost";
+        let file = TempHosts::new(original);
+
+        write_section(&file.0, &hosts(&["reddit.com"])).unwrap();
+        assert!(file.read().starts_with("127.0.0.1 localhost\n# BEGIN"));
+        assert!(file.read().ends_with(SECTION_END));
+
+        write_section(&file.0, &hosts(&["youtube.com", "x.com"])).unwrap();
+        assert!(file.read().starts_with("127.0.0.1 localhost\n# BEGIN"));
+        assert!(!file.read().contains("reddit.com"));
+
+        write_section(&file.0, &[]).unwrap();
+        assert_eq!(file.read(), original);
+    }
+
+    #[test]
+    fn replaces_existing_section_only() {
+        let file = TempHosts::new("127.0.0.1 localhost\n");
+        write_section(&file.0, &hosts(&["reddit.com"])).unwrap();
+        std::fs::write(&file.0, format!("{}10.0.0.1 nas\n", file.read())).unwrap();
+
+        write_section(&file.0, &hosts(&["youtube.com"])).unwrap();
+        let contents = file.read();
+
+        assert!(contents.starts_with("127.0.0.1 localhost\n# BEGIN"));
+        assert!(contents.ends_with(&format!("{}\n10.0.0.1 nas\n", SECTION_END)));
+        assert!(contents.contains("0.0.0.0 youtube.com\n"));
+        assert!(!contents.contains("reddit.com"));
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let updated = replace_section("127.0.0.1 localhost\r\n", &hosts(&["reddit.com"])).unwrap();
+
+        assert!(updated.contains("0.0.0.0 reddit.com\r\n"));
+        assert!(updated.ends_with(&format!("{}\r\n", SECTION_END)));
+        assert!(!updated.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn leaves_unterminated_section_untouched() {
+        let original = format!(
+            "127.0.0.1 localhost\n{}\n0.0.0.0 reddit.com\n",
+            SECTION_BEGIN
+        );
+        let file = TempHosts::new(&original);
+
+        assert!(write_section(&file.0, &hosts(&["youtube.com"])).is_err());
+        assert!(write_section(&file.0, &[]).is_err());
+        assert_eq!(file.read(), original);
+    }
+
+    #[test]
+    fn removes_section_when_empty() {
+        let original = "127.0.0.1 localhost\n::1 localhost\n";
+        let file = TempHosts::new(original);
+        write_section(&file.0, &hosts(&["reddit.com"])).unwrap();
+
+        write_section(&file.0, &[]).unwrap();
+        assert_eq!(file.read(), original);
+    }
+}
@@ -1,8 +1,20 @@
+use chrono::Utc;
+use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::State;
 
+use crate::database::{Database, DbError};
+
+/// activity_logs type for active screen time tracked by the detector.
+pub const SCREEN_TIME_ACTIVITY: &str = "screen_time";
+
+/// Minimum amount of unsaved active time worth writing as its own row.
+const SCREEN_TIME_FLUSH_SECONDS: u64 = 60;
+
+const THRESHOLD_KEY: &str = "idle_threshold_seconds";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActivityState {
     pub is_active: bool,
@@ -26,13 +38,43 @@ impl Default for ActivityState {
 
 pub struct IdleDetector {
     state: Mutex<ActivityState>,
+    flushed_active_seconds: Mutex<u64>,
 }
 
 impl Default for IdleDetector {
     fn default() -> Self {
         IdleDetector {
             state: Mutex::new(ActivityState::default()),
+            flushed_active_seconds: Mutex::new(0),
+        }
+    }
+}
+
+impl IdleDetector {
+    pub fn load_settings(&self, db: &Database) -> Result<(), DbError> {
+        if let Some(threshold) = db.load_setting(THRESHOLD_KEY)? {
+            self.state.lock().unwrap().idle_threshold_seconds = threshold;
+        }
+        Ok(())
+    }
+
+    /// Writes active time accumulated since the last flush to activity_logs
+    /// as screen time, once at least a minute has built up or when forced.
+    fn flush_screen_time(&self, db: &Database, force: bool) -> Result<(), DbError> {
+        let total = self.state.lock().unwrap().total_active_seconds;
+        let mut flushed = self.flushed_active_seconds.lock().unwrap();
+
+        let pending = total.saturating_sub(*flushed);
+        if pending == 0 || (!force && pending < SCREEN_TIME_FLUSH_SECONDS) {
+            return Ok(());
         }
+
+        db.conn().execute(
+            "INSERT INTO activity_logs (timestamp, activity_type, duration) VALUES (?1, ?2, ?3)",
+            params![Utc::now().to_rfc3339(), SCREEN_TIME_ACTIVITY, pending as i64],
+        )?;
+        *flushed = total;
+        Ok(())
     }
 }
 
@@ -55,7 +97,10 @@ pub async fn get_activity_state(
 pub async fn update_idle_threshold(
     threshold_seconds: u64,
     state: State<'_, IdleDetector>,
+    db: State<'_, Database>,
 ) -> Result<(), String> {
+    db.save_setting(THRESHOLD_KEY, &threshold_seconds).map_err(|e| e.to_string())?;
+
     let mut activity_state = state.state.lock().unwrap();
     activity_state.idle_threshold_seconds = threshold_seconds;
     Ok(())
@@ -64,43 +109,63 @@ pub async fn update_idle_threshold(
 #[tauri::command]
 pub async fn record_activity(
     state: State<'_, IdleDetector>,
+    db: State<'_, Database>,
 ) -> Result<ActivityState, String> {
-    let mut activity_state = state.state.lock().unwrap();
-    let current_time = current_timestamp();
-    let elapsed = current_time - activity_state.last_activity_time;
-    
-    // If we were idle, track idle time
-    if !activity_state.is_active && elapsed < activity_state.idle_threshold_seconds {
-        activity_state.total_idle_seconds += elapsed;
-    } else if activity_state.is_active {
-        activity_state.total_active_seconds += elapsed;
-    }
-    
-    activity_state.is_active = true;
-    activity_state.last_activity_time = current_time;
-    
-    Ok(activity_state.clone())
+    let snapshot = {
+        let mut activity_state = state.state.lock().unwrap();
+        let current_time = current_timestamp();
+        let elapsed = current_time - activity_state.last_activity_time;
+
+        // If we were idle, track idle time
+        if !activity_state.is_active && elapsed < activity_state.idle_threshold_seconds {
+            activity_state.total_idle_seconds += elapsed;
+        } else if activity_state.is_active {
+            // A gap longer than the threshold means an idle check was missed,
+            // e.g. while the machine slept; only the threshold counts as active
+            let active = elapsed.min(activity_state.idle_threshold_seconds);
+            activity_state.total_active_seconds += active;
+            activity_state.total_idle_seconds += elapsed - active;
+        }
+
+        activity_state.is_active = true;
+        activity_state.last_activity_time = current_time;
+        activity_state.clone()
+    };
+
+    state.flush_screen_time(&db, false).map_err(|e| e.to_string())?;
+    Ok(snapshot)
 }
 
 #[tauri::command]
 pub async fn check_idle_status(
     state: State<'_, IdleDetector>,
+    db: State<'_, Database>,
 ) -> Result<bool, String> {
-    let mut activity_state = state.state.lock().unwrap();
-    let current_time = current_timestamp();
-    let idle_duration = current_time - activity_state.last_activity_time;
-    
-    let is_now_idle = idle_duration >= activity_state.idle_threshold_seconds;
-    
-    // Update state if changed
-    if is_now_idle && activity_state.is_active {
-        activity_state.is_active = false;
-        activity_state.total_active_seconds += idle_duration;
-    } else if !is_now_idle && !activity_state.is_active {
-        activity_state.is_active = true;
-        activity_state.total_idle_seconds += idle_duration;
-    }
-    
+    let (is_now_idle, went_idle) = {
+        let mut activity_state = state.state.lock().unwrap();
+        let current_time = current_timestamp();
+        let idle_duration = current_time - activity_state.last_activity_time;
+
+        let is_now_idle = idle_duration >= activity_state.idle_threshold_seconds;
+        let went_idle = is_now_idle && activity_state.is_active;
+
+        // Update state if changed
+        if went_idle {
+            activity_state.is_active = false;
+            // Activity stopped at last_activity_time; crediting the whole gap
+            // would count a late check (say, after sleep) as screen time
+            activity_state.total_active_seconds +=
+                idle_duration.min(activity_state.idle_threshold_seconds);
+        } else if !is_now_idle && !activity_state.is_active {
+            activity_state.is_active = true;
+            activity_state.total_idle_seconds += idle_duration;
+        }
+
+        (is_now_idle, went_idle)
+    };
+
+    // Close out the active stretch as soon as the user goes idle
+    state.flush_screen_time(&db, went_idle).map_err(|e| e.to_string())?;
     Ok(is_now_idle)
 }
 
@@ -122,11 +187,16 @@ pub async fn get_idle_duration(
 #[tauri::command]
 pub async fn reset_activity_counters(
     state: State<'_, IdleDetector>,
+    db: State<'_, Database>,
 ) -> Result<(), String> {
+    // Keep unsaved screen time before the counters are cleared
+    state.flush_screen_time(&db, true).map_err(|e| e.to_string())?;
+
     let mut activity_state = state.state.lock().unwrap();
     activity_state.total_active_seconds = 0;
     activity_state.total_idle_seconds = 0;
     activity_state.last_activity_time = current_timestamp();
+    *state.flushed_active_seconds.lock().unwrap() = 0;
     Ok(())
 }
 
@@ -1,5 +1,11 @@
+use chrono::{Duration, Local, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
 use serde::{Deserialize, Serialize};
-use chrono::Utc;
+use tauri::{AppHandle, State};
+
+use crate::database::Database;
+use crate::gamification::{self, PointsReason};
+use crate::stats_tracker::{day_totals, parse_date};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JournalEntry {
@@ -8,6 +14,7 @@ pub struct JournalEntry {
     pub mood: i32,  // 1-5 scale
     pub notes: String,
     pub created_at: String,
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,93 +24,209 @@ pub struct MoodTrend {
     pub screen_time_minutes: i64,
 }
 
+fn validate_mood(mood: i32) -> Result<(), String> {
+    if !(1..=5).contains(&mood) {
+        return Err("Mood must be between 1 and 5".to_string());
+    }
+    Ok(())
+}
+
+fn not_found(id: i64) -> String {
+    format!("Journal entry {} not found", id)
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<JournalEntry> {
+    Ok(JournalEntry {
+        id: Some(row.get(0)?),
+        date: row.get(1)?,
+        mood: row.get(2)?,
+        notes: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+fn find_entry(conn: &Connection, id: i64) -> rusqlite::Result<Option<JournalEntry>> {
+    conn.query_row(
+        "SELECT id, date, mood, notes, created_at, updated_at
+         FROM journal_entries WHERE id = ?1",
+        params![id],
+        entry_from_row,
+    )
+    .optional()
+}
+
 #[tauri::command]
 pub async fn add_journal_entry(
     date: String,
     mood: i32,
     notes: String,
-    _app: tauri::AppHandle,
+    app: AppHandle,
+    db: State<'_, Database>,
 ) -> Result<JournalEntry, String> {
-    if mood < 1 || mood > 5 {
-        return Err("Mood must be between 1 and 5".to_string());
-    }
+    validate_mood(mood)?;
+    // Stored zero-padded so the string comparisons on `date` order correctly
+    let date = parse_date(&date)?.format("%Y-%m-%d").to_string();
 
     let created_at = Utc::now().to_rfc3339();
 
-    // Mock implementation - in production, insert into database
+    let (id, first_of_day) = {
+        let conn = db.conn();
+        let existing: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM journal_entries WHERE date = ?1",
+                params![date],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+
+        conn.execute(
+            "INSERT INTO journal_entries (date, mood, notes, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![date, mood, notes, created_at],
+        )
+        .map_err(|e| e.to_string())?;
+        (conn.last_insert_rowid(), existing == 0)
+    };
+
+    // Only the first entry of a day earns points. The entry is already saved,
+    // so a failed award must not make the caller retry and duplicate it
+    if first_of_day {
+        if let Err(e) =
+            gamification::award_points(&app, PointsReason::JournalEntry, Some(id.to_string()))
+        {
+            eprintln!("Failed to award journal entry points: {}", e);
+        }
+    }
+
     Ok(JournalEntry {
-        id: Some(1),
+        id: Some(id),
         date,
         mood,
         notes,
         created_at,
+        updated_at: None,
     })
 }
 
 #[tauri::command]
 pub async fn get_journal_entries(
-    _start_date: String,
-    _end_date: String,
-    _app: tauri::AppHandle,
+    start_date: String,
+    end_date: String,
+    db: State<'_, Database>,
 ) -> Result<Vec<JournalEntry>, String> {
-    // Mock implementation
-    Ok(vec![
-        JournalEntry {
-            id: Some(1),
-            date: "2026-02-12".to_string(),
-            mood: 4,
-            notes: "Productive day! Completed 4 focus sessions.".to_string(),
-            created_at: Utc::now().to_rfc3339(),
-        },
-    ])
+    let start = parse_date(&start_date)?;
+    let end = parse_date(&end_date)?;
+    if end < start {
+        return Err("End date must not be before start date".to_string());
+    }
+
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, date, mood, notes, created_at, updated_at
+             FROM journal_entries
+             WHERE date >= ?1 AND date <= ?2
+             ORDER BY date DESC, id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let entries = stmt
+        .query_map(
+            params![
+                start.format("%Y-%m-%d").to_string(),
+                end.format("%Y-%m-%d").to_string()
+            ],
+            entry_from_row,
+        )
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        .map_err(|e| e.to_string())?;
+
+    Ok(entries)
 }
 
 #[tauri::command]
 pub async fn update_journal_entry(
-    _id: i64,
+    id: i64,
     mood: i32,
     notes: String,
-    _app: tauri::AppHandle,
+    db: State<'_, Database>,
 ) -> Result<JournalEntry, String> {
-    if mood < 1 || mood > 5 {
-        return Err("Mood must be between 1 and 5".to_string());
+    validate_mood(mood)?;
+
+    let conn = db.conn();
+    let updated = conn
+        .execute(
+            "UPDATE journal_entries SET mood = ?1, notes = ?2, updated_at = ?3 WHERE id = ?4",
+            params![mood, notes, Utc::now().to_rfc3339(), id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err(not_found(id));
     }
 
-    // Mock implementation
-    Ok(JournalEntry {
-        id: Some(1),
-        date: "2026-02-12".to_string(),
-        mood,
-        notes,
-        created_at: Utc::now().to_rfc3339(),
-    })
+    find_entry(&conn, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| not_found(id))
 }
 
 #[tauri::command]
 pub async fn delete_journal_entry(
-    _id: i64,
-    _app: tauri::AppHandle,
+    id: i64,
+    db: State<'_, Database>,
 ) -> Result<bool, String> {
-    // Mock implementation
+    let deleted = db
+        .conn()
+        .execute("DELETE FROM journal_entries WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    if deleted == 0 {
+        return Err(not_found(id));
+    }
     Ok(true)
 }
 
 #[tauri::command]
 pub async fn get_mood_trends(
-    _days: i32,
-    _app: tauri::AppHandle,
+    days: i32,
+    db: State<'_, Database>,
 ) -> Result<Vec<MoodTrend>, String> {
-    // Mock implementation - in production, aggregate from database
-    Ok(vec![
-        MoodTrend {
-            date: "2026-02-12".to_string(),
-            average_mood: 4.0,
-            screen_time_minutes: 120,
-        },
-        MoodTrend {
-            date: "2026-02-11".to_string(),
-            average_mood: 3.5,
-            screen_time_minutes: 180,
-        },
-    ])
+    if !(1..=365).contains(&days) {
+        return Err("Days must be between 1 and 365".to_string());
+    }
+
+    let end = Local::now().date_naive();
+    let start = end - Duration::days(i64::from(days) - 1);
+
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT date, AVG(mood)
+             FROM journal_entries
+             WHERE date >= ?1 AND date <= ?2
+             GROUP BY date
+             ORDER BY date ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let moods = stmt
+        .query_map(
+            params![start.format("%Y-%m-%d").to_string(), end.format("%Y-%m-%d").to_string()],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)),
+        )
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        .map_err(|e| e.to_string())?;
+
+    moods
+        .into_iter()
+        .map(|(date, average_mood)| {
+            let day = parse_date(&date)?;
+            let totals = day_totals(&conn, day).map_err(|e| e.to_string())?;
+            Ok(MoodTrend {
+                date,
+                average_mood: (average_mood * 100.0).round() / 100.0,
+                screen_time_minutes: totals.active_seconds() / 60,
+            })
+        })
+        .collect()
 }
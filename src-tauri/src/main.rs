@@ -9,10 +9,21 @@ mod database;
 mod distraction_blocker;
 mod health_reminders;
 mod idle_detector;
+mod url_matcher;
+mod block_schedule;
+mod blocker_server;
+mod app_blocker;
+mod hosts_blocker;
+mod blocklist_io;
+mod distraction_log;
+mod distraction_catalog;
+mod override_friction;
+
+use tauri::Manager;
 
 #[tauri::command]
-async fn init_database(app: tauri::AppHandle) -> Result<(), String> {
-    database::init_db(&app).await.map_err(|e| e.to_string())
+async fn init_database(db: tauri::State<'_, database::Database>) -> Result<i64, String> {
+    db.migrate().map_err(|e| e.to_string())
 }
 
 fn main() {
@@ -24,14 +35,32 @@ fn main() {
         .manage(distraction_blocker::BlockerManager::default())
         .manage(health_reminders::ReminderManager::default())
         .manage(idle_detector::IdleDetector::default())
+        .manage(gamification::GamificationManager::default())
+        .manage(blocker_server::BlockerServer::default())
+        .manage(app_blocker::AppWatcher::default())
+        .manage(hosts_blocker::HostsBlocker::default())
         .setup(|app| {
-            // Initialize database on startup
-            let handle = app.handle().clone();
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = database::init_db(&handle).await {
-                    eprintln!("Failed to initialize database: {}", e);
-                }
-            });
+            // Open the database before any command can reach it
+            let db = database::init_db(app.handle())?;
+
+            // Restore persisted settings over the defaults
+            app.state::<focus_manager::FocusManager>().load_settings(&db)?;
+            app.state::<distraction_blocker::BlockerManager>().load_settings(&db)?;
+            app.state::<health_reminders::ReminderManager>().load_settings(&db)?;
+            app.state::<idle_detector::IdleDetector>().load_settings(&db)?;
+            app.state::<gamification::GamificationManager>().load_settings(&db)?;
+            app.state::<blocker_server::BlockerServer>().load_settings(&db)?;
+            app.state::<hosts_blocker::HostsBlocker>().load_settings(&db)?;
+            app_blocker::resume_orphaned(&db);
+
+            app.manage(db);
+
+            // A taken port shouldn't stop the app; the user can pick another
+            if let Err(e) = app.state::<blocker_server::BlockerServer>().start(app.handle()) {
+                eprintln!("{}", e);
+            }
+            app_blocker::start_watcher(app.handle());
+            hosts_blocker::start_sync(app.handle());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -59,16 +88,42 @@ fn main() {
             gamification::get_achievements,
             gamification::get_user_stats,
             gamification::check_new_achievements,
+            gamification::get_points_history,
+            gamification::get_level_curve,
+            gamification::update_level_curve,
             // Distraction Blocker
             distraction_blocker::get_blocker_settings,
             distraction_blocker::update_blocker_settings,
             distraction_blocker::add_blocked_item,
             distraction_blocker::remove_blocked_item,
             distraction_blocker::toggle_blocked_item,
+            distraction_blocker::add_allowed_item,
+            distraction_blocker::remove_allowed_item,
+            distraction_blocker::toggle_allowed_item,
+            distraction_blocker::set_item_schedule,
             distraction_blocker::check_url_blocked,
             distraction_blocker::request_override,
+            distraction_blocker::begin_override,
+            distraction_blocker::complete_override,
+            distraction_blocker::get_override_status,
             distraction_blocker::end_override,
-            distraction_blocker::get_popular_distractions,
+            blocklist_io::import_blocklist,
+            blocklist_io::export_blocklist,
+            // Distraction Catalog
+            distraction_catalog::get_popular_distractions,
+            distraction_catalog::get_distraction_catalog,
+            distraction_catalog::add_catalog_category,
+            // Distraction Log
+            distraction_log::get_top_distractions,
+            distraction_log::get_distraction_stats,
+            // Blocker Server
+            blocker_server::get_blocker_server_settings,
+            blocker_server::update_blocker_server_settings,
+            blocker_server::regenerate_blocker_server_token,
+            // Hosts Blocker
+            hosts_blocker::get_hosts_blocker_settings,
+            hosts_blocker::update_hosts_blocker_settings,
+            hosts_blocker::get_hosts_blocker_status,
             // Health Reminders
             health_reminders::get_reminder_settings,
             health_reminders::update_reminder_settings,
@@ -76,6 +131,7 @@ fn main() {
             health_reminders::update_reminder,
             health_reminders::delete_reminder,
             health_reminders::toggle_reminder,
+            health_reminders::honor_reminder,
             health_reminders::get_due_reminders,
             // Idle Detector
             idle_detector::get_activity_state,
@@ -86,6 +142,13 @@ fn main() {
             idle_detector::reset_activity_counters,
             idle_detector::get_today_active_time,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                focus_manager::interrupt_active_session(app);
+                app_blocker::resume_suspended(app);
+                hosts_blocker::clear(app);
+            }
+        });
 }
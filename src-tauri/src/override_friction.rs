@@ -0,0 +1,163 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How long a challenge stays valid once its wait is over.
+const CHALLENGE_TTL_SECONDS: i64 = 300;
+
+const MAX_WAIT_SECONDS: u32 = 3600;
+const MAX_PHRASE_WORDS: u32 = 20;
+const MIN_REASON_CHARS: usize = 10;
+
+/// Phrase vocabulary. 64 words, so each random byte picks one without bias.
+const WORDS: &[&str] = &[
+    "amber", "anchor", "autumn", "basket", "breeze", "canyon", "cedar", "copper",
+    "cotton", "crystal", "desert", "ember", "falcon", "feather", "garden", "glacier",
+    "granite", "harbor", "hollow", "island", "ivory", "jasmine", "lantern", "maple",
+    "marble", "meadow", "mirror", "morning", "orchard", "pebble", "pepper", "planet",
+    "quartz", "quiet", "rabbit", "river", "saddle", "salmon", "shadow", "silver",
+    "spruce", "summit", "thistle", "thunder", "timber", "tundra", "valley", "velvet",
+    "walnut", "willow", "winter", "yellow", "zephyr", "almond", "bamboo", "candle",
+    "dolphin", "forest", "harvest", "lemon", "nectar", "olive", "prairie", "sparrow",
+];
+
+/// Hurdles between asking for an override and getting one. All off by default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverrideFriction {
+    /// Countdown before the override can be confirmed.
+    pub wait_seconds: u32,
+    /// Words in a random phrase that must be typed back; 0 for none.
+    pub phrase_words: u32,
+    /// Whether a reason must be given. Reasons are logged either way.
+    pub require_reason: bool,
+}
+
+/// What the user has to do to complete an override, as shown to them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverrideChallenge {
+    pub id: String,
+    /// Seconds the override will last once granted.
+    pub duration: u32,
+    pub ready_at: String,
+    pub expires_at: String,
+    pub phrase: Option<String>,
+    pub reason_required: bool,
+}
+
+/// A challenge waiting to be completed.
+pub struct PendingChallenge {
+    pub challenge: OverrideChallenge,
+    ready_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+impl OverrideFriction {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.wait_seconds > MAX_WAIT_SECONDS {
+            return Err(format!(
+                "Override wait cannot exceed {} seconds",
+                MAX_WAIT_SECONDS
+            ));
+        }
+        if self.phrase_words > MAX_PHRASE_WORDS {
+            return Err(format!(
+                "Override phrase cannot exceed {} words",
+                MAX_PHRASE_WORDS
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.wait_seconds > 0 || self.phrase_words > 0 || self.require_reason
+    }
+
+    /// Starts a challenge for an override of `duration` seconds.
+    pub fn new_challenge(&self, duration: u32, now: DateTime<Utc>) -> PendingChallenge {
+        let ready_at = now + Duration::seconds(i64::from(self.wait_seconds));
+        let expires_at = ready_at + Duration::seconds(CHALLENGE_TTL_SECONDS);
+
+        PendingChallenge {
+            challenge: OverrideChallenge {
+                id: uuid::Uuid::new_v4().to_string(),
+                duration,
+                ready_at: ready_at.to_rfc3339(),
+                expires_at: expires_at.to_rfc3339(),
+                phrase: (self.phrase_words > 0).then(|| random_phrase(self.phrase_words)),
+                reason_required: self.require_reason,
+            },
+            ready_at,
+            expires_at,
+        }
+    }
+}
+
+impl PendingChallenge {
+    /// Checks the user's answers to this challenge at `now`.
+    pub fn verify(
+        &self,
+        id: &str,
+        phrase: Option<&str>,
+        reason: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<(), String> {
+        if id != self.challenge.id {
+            return Err("Override challenge not found; start a new one".to_string());
+        }
+        if now > self.expires_at {
+            return Err("Override challenge expired; start a new one".to_string());
+        }
+        if now < self.ready_at {
+            let remaining = (self.ready_at - now).num_seconds() + 1;
+            return Err(format!("Wait {} more seconds before overriding", remaining));
+        }
+
+        if let Some(expected) = &self.challenge.phrase {
+            let typed = phrase.map(normalize_phrase).unwrap_or_default();
+            if typed != normalize_phrase(expected) {
+                return Err("The phrase does not match".to_string());
+            }
+        }
+
+        if self.challenge.reason_required
+            && reason.map_or(0, |r| r.trim().chars().count()) < MIN_REASON_CHARS
+        {
+            return Err(format!(
+                "Give a reason of at least {} characters",
+                MIN_REASON_CHARS
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn normalize_phrase(phrase: &str) -> String {
+    phrase
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Picks words using random v4 UUIDs as the entropy source.
+fn random_phrase(words: u32) -> String {
+    let mut picked = Vec::new();
+    while picked.len() < words as usize {
+        let uuid = uuid::Uuid::new_v4();
+        // Byte 6's high nibble is the fixed version (4) and `% 64` keeps the
+        // low six bits, two of which are version bits, so that byte is
+        // skipped. Byte 8's fixed variant bits are its top two, which `% 64`
+        // drops, so it stays
+        for (index, byte) in uuid.as_bytes().iter().enumerate() {
+            if index == 6 {
+                continue;
+            }
+            if picked.len() == words as usize {
+                break;
+            }
+            picked.push(WORDS[*byte as usize % WORDS.len()]);
+        }
+    }
+    picked.join(" ")
+}
@@ -1,4 +1,10 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, SecondsFormat, TimeZone, Utc};
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::database::Database;
+use crate::idle_detector::SCREEN_TIME_ACTIVITY;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActivityLog {
@@ -27,18 +33,109 @@ pub struct WeeklyStats {
     pub average_daily_focus: f64,
 }
 
+pub fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", date))
+}
+
+fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+    // Midnight can be skipped by a DST jump; the day then starts an hour later
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| Local.from_local_datetime(&(midnight + Duration::hours(1))).earliest())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+/// UTC bounds `[start, end)` of a local calendar day, formatted to compare
+/// against the RFC 3339 UTC timestamps stored in activity_logs.
+pub fn local_day_bounds(date: NaiveDate) -> (String, String) {
+    let start = local_midnight(date);
+    let end = local_midnight(date + Duration::days(1));
+    (
+        start.to_rfc3339_opts(SecondsFormat::Secs, false),
+        end.to_rfc3339_opts(SecondsFormat::Secs, false),
+    )
+}
+
+/// Raw per-day totals from activity_logs. Durations are stored in seconds.
+pub struct DayTotals {
+    pub focus_seconds: i64,
+    pub break_seconds: i64,
+    pub completed_sessions: i64,
+    pub screen_seconds: i64,
+}
+
+impl DayTotals {
+    /// Screen time tracked by the idle detector, falling back to logged
+    /// session time on days the detector recorded nothing.
+    pub fn active_seconds(&self) -> i64 {
+        if self.screen_seconds > 0 {
+            self.screen_seconds
+        } else {
+            self.focus_seconds + self.break_seconds
+        }
+    }
+}
+
+pub fn day_totals(conn: &Connection, date: NaiveDate) -> rusqlite::Result<DayTotals> {
+    let (start, end) = local_day_bounds(date);
+
+    conn.query_row(
+        "SELECT
+             COALESCE(SUM(CASE WHEN activity_type = 'focus' THEN duration ELSE 0 END), 0),
+             COALESCE(SUM(CASE WHEN activity_type IN ('short_break', 'long_break')
+                               THEN duration ELSE 0 END), 0),
+             COALESCE(SUM(CASE WHEN activity_type = 'focus' AND outcome = 'completed'
+                               THEN 1 ELSE 0 END), 0),
+             COALESCE(SUM(CASE WHEN activity_type = ?3 THEN duration ELSE 0 END), 0)
+         FROM activity_logs
+         WHERE timestamp >= ?1 AND timestamp < ?2",
+        params![start, end, SCREEN_TIME_ACTIVITY],
+        |row| {
+            Ok(DayTotals {
+                focus_seconds: row.get(0)?,
+                break_seconds: row.get(1)?,
+                completed_sessions: row.get(2)?,
+                screen_seconds: row.get(3)?,
+            })
+        },
+    )
+}
+
+pub fn daily_stats(conn: &Connection, date: NaiveDate) -> rusqlite::Result<DailyStats> {
+    let totals = day_totals(conn, date)?;
+
+    Ok(DailyStats {
+        date: date.format("%Y-%m-%d").to_string(),
+        total_focus_minutes: totals.focus_seconds / 60,
+        total_break_minutes: totals.break_seconds / 60,
+        completed_sessions: totals.completed_sessions,
+        active_hours: (totals.active_seconds() as f64 / 3600.0 * 100.0).round() / 100.0,
+    })
+}
+
 #[tauri::command]
 pub async fn log_activity(
     activity_type: String,
     duration: i64,
     metadata: Option<String>,
-    _app: tauri::AppHandle,
+    db: State<'_, Database>,
 ) -> Result<ActivityLog, String> {
     let timestamp = Utc::now().to_rfc3339();
 
-    // In a real implementation, this would insert into the database
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO activity_logs (timestamp, activity_type, duration, metadata)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![timestamp, activity_type, duration, metadata],
+    )
+    .map_err(|e| e.to_string())?;
+
     Ok(ActivityLog {
-        id: Some(1),
+        id: Some(conn.last_insert_rowid()),
         timestamp,
         activity_type,
         duration,
@@ -49,53 +146,138 @@ pub async fn log_activity(
 #[tauri::command]
 pub async fn get_daily_stats(
     date: String,
-    _app: tauri::AppHandle,
+    db: State<'_, Database>,
 ) -> Result<DailyStats, String> {
-    // Mock implementation - in production, query from database
-    Ok(DailyStats {
-        date: date.clone(),
-        total_focus_minutes: 120,
-        total_break_minutes: 30,
-        completed_sessions: 4,
-        active_hours: 2.5,
-    })
+    let date = parse_date(&date)?;
+    daily_stats(&db.conn(), date).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn get_weekly_stats(
     week_start: String,
-    _app: tauri::AppHandle,
+    db: State<'_, Database>,
 ) -> Result<WeeklyStats, String> {
-    // Mock implementation
-    let daily_stats = vec![
-        DailyStats {
-            date: week_start.clone(),
-            total_focus_minutes: 120,
-            total_break_minutes: 30,
-            completed_sessions: 4,
-            active_hours: 2.5,
-        },
-    ];
+    let start = parse_date(&week_start)?;
+
+    let conn = db.conn();
+    let daily_stats = (0..7)
+        .map(|offset| daily_stats(&conn, start + Duration::days(offset)))
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+
+    let total_focus_minutes: i64 = daily_stats.iter().map(|d| d.total_focus_minutes).sum();
+    let total_sessions = daily_stats.iter().map(|d| d.completed_sessions).sum();
 
     Ok(WeeklyStats {
         week_start,
-        daily_stats: daily_stats.clone(),
-        total_focus_minutes: 120,
-        total_sessions: 4,
-        average_daily_focus: 120.0,
+        average_daily_focus: total_focus_minutes as f64 / daily_stats.len() as f64,
+        daily_stats,
+        total_focus_minutes,
+        total_sessions,
     })
 }
 
+const MAX_EXPORT_DAYS: i64 = 366;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            _ => Err(format!(
+                "Unsupported export format '{}', expected csv, json or markdown",
+                value
+            )),
+        }
+    }
+}
+
+fn export_csv(days: &[DailyStats]) -> String {
+    let mut out = String::from("Date,Focus Minutes,Break Minutes,Sessions,Active Hours\n");
+    for day in days {
+        out.push_str(&format!(
+            "{},{},{},{},{}\n",
+            day.date,
+            day.total_focus_minutes,
+            day.total_break_minutes,
+            day.completed_sessions,
+            day.active_hours
+        ));
+    }
+    out
+}
+
+fn export_markdown(start: NaiveDate, end: NaiveDate, days: &[DailyStats]) -> String {
+    let focus: i64 = days.iter().map(|d| d.total_focus_minutes).sum();
+    let breaks: i64 = days.iter().map(|d| d.total_break_minutes).sum();
+    let sessions: i64 = days.iter().map(|d| d.completed_sessions).sum();
+    let hours: f64 = days.iter().map(|d| d.active_hours).sum();
+
+    let mut out = format!("# MindfulDesk Report: {} to {}\n\n", start, end);
+    out.push_str(&format!(
+        "- **Focus time:** {}h {}m\n- **Completed sessions:** {}\n- **Average daily focus:** {:.1} minutes\n\n",
+        focus / 60,
+        focus % 60,
+        sessions,
+        focus as f64 / days.len() as f64
+    ));
+    out.push_str("| Date | Focus (min) | Break (min) | Sessions | Active Hours |\n");
+    out.push_str("|------|------------:|------------:|---------:|-------------:|\n");
+    for day in days {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {:.2} |\n",
+            day.date,
+            day.total_focus_minutes,
+            day.total_break_minutes,
+            day.completed_sessions,
+            day.active_hours
+        ));
+    }
+    out.push_str(&format!(
+        "| **Total** | **{}** | **{}** | **{}** | **{:.2}** |\n",
+        focus, breaks, sessions, hours
+    ));
+    out
+}
+
 #[tauri::command]
 pub async fn export_stats(
-    _start_date: String,
-    _end_date: String,
-    _format: String,
-    _app: tauri::AppHandle,
+    start_date: String,
+    end_date: String,
+    format: String,
+    db: State<'_, Database>,
 ) -> Result<String, String> {
-    // Mock CSV export
-    let csv_data = "Date,Focus Minutes,Break Minutes,Sessions,Active Hours\n\
-                    2026-01-01,120,30,4,2.5\n";
-    
-    Ok(csv_data.to_string())
+    let format = ExportFormat::parse(&format)?;
+    let start = parse_date(&start_date)?;
+    let end = parse_date(&end_date)?;
+
+    if end < start {
+        return Err("End date must not be before start date".to_string());
+    }
+    let day_count = (end - start).num_days() + 1;
+    if day_count > MAX_EXPORT_DAYS {
+        return Err(format!("Export range cannot exceed {} days", MAX_EXPORT_DAYS));
+    }
+
+    let days = {
+        let conn = db.conn();
+        (0..day_count)
+            .map(|offset| daily_stats(&conn, start + Duration::days(offset)))
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())?
+    };
+
+    match format {
+        ExportFormat::Csv => Ok(export_csv(&days)),
+        ExportFormat::Json => serde_json::to_string_pretty(&days).map_err(|e| e.to_string()),
+        ExportFormat::Markdown => Ok(export_markdown(start, end, &days)),
+    }
 }
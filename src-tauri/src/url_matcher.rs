@@ -0,0 +1,286 @@
+use std::fmt;
+use url::Url;
+
+/// Host part of a blocking pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostRule {
+    /// `*` — every host.
+    Any,
+    /// `example.com` — that host, with or without a leading `www.`.
+    Exact(String),
+    /// `*.example.com` — the domain and all of its subdomains.
+    Suffix(String),
+    /// Any other pattern containing `*`, e.g. `reddit.*`.
+    Glob(String),
+}
+
+/// A parsed `BlockedItem::url_pattern`, e.g. `*.reddit.com` or `youtube.com/shorts/*`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlPattern {
+    pub host: HostRule,
+    /// Path prefix, or a glob when it contains `*`.
+    pub path: Option<String>,
+}
+
+/// The parts of a URL that patterns are matched against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedUrl {
+    pub host: String,
+    pub path: String,
+}
+
+impl ParsedUrl {
+    /// Parses a full URL or a bare `host/path`, as typed into an address bar.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        let url = match Url::parse(input) {
+            Ok(url) if url.has_host() => url,
+            _ => Url::parse(&format!("http://{}", input))
+                .map_err(|_| format!("Invalid URL '{}'", input))?,
+        };
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| format!("URL '{}' has no host", input))?;
+
+        Ok(ParsedUrl {
+            host: normalize_host(host),
+            path: url.path().to_lowercase(),
+        })
+    }
+}
+
+impl UrlPattern {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let trimmed = pattern.trim();
+        let without_scheme = trimmed.split_once("://").map_or(trimmed, |(_, rest)| rest);
+
+        let (host, path) = match without_scheme.find('/') {
+            Some(index) => (&without_scheme[..index], Some(&without_scheme[index..])),
+            None => (without_scheme, None),
+        };
+
+        let host = normalize_host(host);
+        if host.is_empty() {
+            return Err(format!("Pattern '{}' has no host", pattern));
+        }
+        if host
+            .chars()
+            .any(|c| c.is_whitespace() || c == '?' || c == '#')
+        {
+            return Err(format!("Pattern '{}' has an invalid host", pattern));
+        }
+        // Ports are never compared, so a pattern with one could not match.
+        // Colons inside an IPv6 literal such as [::1] are fine.
+        if host
+            .rsplit(']')
+            .next()
+            .is_some_and(|rest| rest.contains(':'))
+        {
+            return Err(format!(
+                "Pattern '{}' has a port; ports are not supported",
+                pattern
+            ));
+        }
+
+        let host = if host == "*" {
+            HostRule::Any
+        } else if let Some(domain) = host.strip_prefix("*.").filter(|d| !d.contains('*')) {
+            HostRule::Suffix(domain.to_string())
+        } else if host.contains('*') {
+            HostRule::Glob(host)
+        } else {
+            HostRule::Exact(host.strip_prefix("www.").unwrap_or(&host).to_string())
+        };
+
+        // A bare "/" or "/*" restricts nothing
+        let path = path
+            .map(|p| p.to_lowercase())
+            .filter(|p| p != "/" && p != "/*");
+
+        Ok(UrlPattern { host, path })
+    }
+
+    /// Explains why `url` matches this pattern, or `None` if it does not.
+    pub fn matches(&self, url: &ParsedUrl) -> Option<String> {
+        let host_reason = match &self.host {
+            HostRule::Any => "any host".to_string(),
+            HostRule::Exact(host) => {
+                let bare = url.host.strip_prefix("www.").unwrap_or(&url.host);
+                if bare != host {
+                    return None;
+                }
+                format!("host is {}", host)
+            }
+            HostRule::Suffix(domain) => {
+                if url.host != *domain && !url.host.ends_with(&format!(".{}", domain)) {
+                    return None;
+                }
+                format!("host is within {}", domain)
+            }
+            HostRule::Glob(glob) => {
+                if !glob_match(glob, &url.host) {
+                    return None;
+                }
+                format!("host matches {}", glob)
+            }
+        };
+
+        match &self.path {
+            None => Some(host_reason),
+            Some(path) if path_matches(path, &url.path) => {
+                Some(format!("{} and path matches {}", host_reason, path))
+            }
+            Some(_) => None,
+        }
+    }
+
+    /// Hostnames a hosts file can block for this pattern. Wildcard hosts and
+    /// path rules can't be expressed there, and `*.domain` only covers the
+    /// domain and its `www.` host.
+    pub fn blockable_hosts(&self) -> Vec<String> {
+        if self.path.is_some() {
+            return vec![];
+        }
+        let domain = match &self.host {
+            HostRule::Exact(domain) | HostRule::Suffix(domain) => domain,
+            HostRule::Any | HostRule::Glob(_) => return vec![],
+        };
+        if !domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+        {
+            return vec![];
+        }
+
+        vec![domain.clone(), format!("www.{}", domain)]
+    }
+}
+
+/// Canonical form of the pattern, so equivalent patterns print the same.
+impl fmt::Display for UrlPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.host {
+            HostRule::Any => write!(f, "*")?,
+            HostRule::Exact(host) | HostRule::Glob(host) => write!(f, "{}", host)?,
+            HostRule::Suffix(domain) => write!(f, "*.{}", domain)?,
+        }
+        if let Some(path) = &self.path {
+            write!(f, "{}", path)?;
+        }
+        Ok(())
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim().trim_end_matches('.').to_lowercase()
+}
+
+/// Globs match anywhere `*` appears; plain paths match themselves and anything
+/// below them, so `/shorts` covers `/shorts/abc` but not `/shortstop`.
+fn path_matches(pattern: &str, path: &str) -> bool {
+    if pattern.contains('*') {
+        return glob_match(pattern, path);
+    }
+
+    let prefix = pattern.trim_end_matches('/');
+    path == prefix || path.starts_with(&format!("{}/", prefix))
+}
+
+/// Matches `text` against a pattern where `*` stands for any run of characters.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last `*` swallow one more character and retry
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, url: &str) -> bool {
+        UrlPattern::parse(pattern)
+            .unwrap()
+            .matches(&ParsedUrl::parse(url).unwrap())
+            .is_some()
+    }
+
+    #[test]
+    fn exact_host_matches_www_but_not_other_domains() {
+        assert!(matches("x.com", "https://x.com/home"));
+        assert!(matches("x.com", "https://www.x.com/home"));
+        assert!(!matches("x.com", "https://dropbox.com"));
+        assert!(!matches("x.com", "https://api.x.com"));
+    }
+
+    #[test]
+    fn suffix_matches_domain_and_subdomains() {
+        assert!(matches("*.reddit.com", "reddit.com"));
+        assert!(matches("*.reddit.com", "https://old.reddit.com/r/rust"));
+        assert!(!matches("*.reddit.com", "https://notreddit.com"));
+    }
+
+    #[test]
+    fn path_prefix_and_glob() {
+        assert!(matches(
+            "youtube.com/shorts/*",
+            "www.youtube.com/shorts/abc"
+        ));
+        assert!(!matches("youtube.com/shorts/*", "youtube.com/watch?v=1"));
+        assert!(matches("youtube.com/shorts", "youtube.com/shorts/abc"));
+        assert!(!matches("youtube.com/shorts", "youtube.com/shortstop"));
+    }
+
+    #[test]
+    fn parses_schemes_globs_and_empty_patterns() {
+        assert!(matches(
+            "https://news.ycombinator.com/",
+            "news.ycombinator.com/item"
+        ));
+        assert!(matches("reddit.*", "https://reddit.co.uk/"));
+        assert!(matches("*", "anything.org"));
+        assert!(UrlPattern::parse("").is_err());
+        assert_eq!(
+            UrlPattern::parse("WWW.Reddit.com/").unwrap().to_string(),
+            "reddit.com"
+        );
+    }
+
+    #[test]
+    fn rejects_ports_but_not_ipv6_literals() {
+        assert!(UrlPattern::parse("localhost:3000").is_err());
+        assert!(UrlPattern::parse("http://[::1]:8080/admin").is_err());
+        assert!(matches("[::1]", "http://[::1]:8080/"));
+    }
+
+    #[test]
+    fn glob_backtracks() {
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(glob_match("*ab", "aaab"));
+        assert!(glob_match("*.example.*", "www.example.co.uk"));
+        assert!(glob_match("**", ""));
+        assert!(!glob_match("a*b", "aXbY"));
+        assert!(!glob_match("*abc", "abab"));
+    }
+}